@@ -0,0 +1,148 @@
+use crate::vec_deque::VecDeque;
+use alloc::collections::TryReserveError;
+use core::alloc::Allocator;
+use core::ops::Deref;
+
+/// A deque with a fixed capacity that never grows.
+///
+/// All the memory is allocated up front by [`BoundedVecDeque::with_capacity_in`].
+/// When full, [`BoundedVecDeque::push_back`] hands the item back, whereas
+/// [`BoundedVecDeque::force_push_back`] evicts the front element to make room.
+pub struct BoundedVecDeque<T, A: Allocator> {
+    deque: VecDeque<T, A>,
+    capacity: usize,
+}
+
+impl<T, A: Allocator> BoundedVecDeque<T, A> {
+    /// Creates an empty deque holding at most `capacity` elements.
+    /// This is the only allocation the deque ever makes.
+    #[inline]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+        Ok(Self {
+            deque: VecDeque::with_capacity_in(capacity, alloc)?,
+            capacity,
+        })
+    }
+
+    /// The maximum number of elements, as requested at construction.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.deque.len() == self.capacity
+    }
+
+    /// Appends `item`, or returns it if the deque is full.
+    #[inline]
+    #[must_use = "the item is handed back when the deque is full"]
+    pub fn push_back(&mut self, item: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(item);
+        }
+        self.push_back_unchecked(item);
+        Ok(())
+    }
+
+    /// Appends `item`, first evicting and returning the front element if the deque is full.
+    /// With a capacity of zero `item` itself is returned.
+    pub fn force_push_back(&mut self, item: T) -> Option<T> {
+        if self.capacity == 0 {
+            return Some(item);
+        }
+        let evicted = if self.is_full() {
+            self.deque.pop_front()
+        } else {
+            None
+        };
+        self.push_back_unchecked(item);
+        evicted
+    }
+
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.deque.pop_front()
+    }
+
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.deque.pop_back()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.deque.clear()
+    }
+
+    fn push_back_unchecked(&mut self, item: T) {
+        // Can't allocate: the length is below the capacity reserved at construction.
+        let pushed = self.deque.push_back(item);
+        debug_assert!(pushed.is_ok());
+    }
+}
+
+impl<T, A: Allocator> Deref for BoundedVecDeque<T, A> {
+    type Target = VecDeque<T, A>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.deque
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{CountingAllocator, NoGlobalAllocGuard, WatermarkAllocator};
+
+    #[test]
+    fn test_push_back_when_full() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let mut deque = BoundedVecDeque::with_capacity_in(3, ca.clone()).unwrap();
+        assert_eq!(deque.capacity(), 3);
+        assert_eq!(ca.allocations(), 1);
+
+        for i in 0..3 {
+            deque.push_back(i).unwrap();
+        }
+        assert!(deque.is_full());
+        assert_eq!(deque.push_back(3), Err(3));
+        assert!(deque.iter().copied().eq(0..3));
+
+        assert_eq!(deque.pop_front(), Some(0));
+        deque.push_back(3).unwrap();
+        assert!(deque.iter().copied().eq(1..4));
+        assert_eq!(ca.allocations(), 1);
+        assert_eq!(ca.grows(), 0);
+    }
+
+    #[test]
+    fn test_force_push_back() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let mut deque = BoundedVecDeque::with_capacity_in(3, ca.clone()).unwrap();
+        assert_eq!(deque.force_push_back(0), None);
+        assert_eq!(deque.force_push_back(1), None);
+        assert_eq!(deque.force_push_back(2), None);
+        assert_eq!(deque.force_push_back(3), Some(0));
+        assert_eq!(deque.force_push_back(4), Some(1));
+        assert!(deque.iter().copied().eq(2..5));
+        assert_eq!(ca.allocations(), 1);
+        assert_eq!(ca.grows(), 0);
+
+        let mut empty = BoundedVecDeque::with_capacity_in(0, ca.clone()).unwrap();
+        assert_eq!(empty.push_back(1), Err(1));
+        assert_eq!(empty.force_push_back(1), Some(1));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_with_capacity_in_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(16);
+        assert!(BoundedVecDeque::<u32, _>::with_capacity_in(5, wma.clone()).is_err());
+        assert_eq!(wma.in_use(), 0);
+    }
+}
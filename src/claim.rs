@@ -2,6 +2,7 @@ use alloc::rc::Rc;
 use alloc::sync::Arc;
 use core::convert::Infallible;
 use core::marker::PhantomData;
+use core::ops::Bound;
 
 /// A marker trait for infallible cloneable objects.
 /// Only implement this for your type if you can guarantee that cloning it
@@ -12,6 +13,31 @@ use core::marker::PhantomData;
 /// blog post.
 pub trait Claim: Clone {}
 
+/// Implements [`Claim`] for one or more `Copy` types, typically small newtype wrappers.
+///
+/// ```
+/// #[derive(Clone, Copy)]
+/// struct Id(u32);
+///
+/// alloc_checked::claim_copy!(Id);
+/// ```
+///
+/// Each type is checked to be `Copy` at compile time. The caller must still ensure
+/// that its `Clone` impl can't panic, e.g. by deriving it: the macro can't see a
+/// custom `Clone` impl.
+#[macro_export]
+macro_rules! claim_copy {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            const _: () = {
+                fn assert_copy<T: ::core::marker::Copy>() {}
+                let _ = assert_copy::<$t>;
+            };
+            impl $crate::claim::Claim for $t {}
+        )+
+    };
+}
+
 // Anything which is trivially copiable is automatically infallible
 // We need to list these out since the compiler will not allow us to `impl <T: Copy> impl Claim {}`
 macro_rules! impl_claim_for {
@@ -41,3 +67,10 @@ impl<T> Claim for Rc<T> {}
 impl Claim for Infallible {}
 impl<T: Claim> Claim for Option<T> {}
 impl<T: Claim, E: Claim> Claim for Result<T, E> {}
+impl<T: Claim> Claim for Bound<T> {}
+
+#[cfg(feature = "either")]
+impl<L: Claim, R: Claim> Claim for either::Either<L, R> {}
+
+#[cfg(feature = "uuid")]
+impl Claim for uuid::Uuid {}
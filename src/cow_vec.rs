@@ -0,0 +1,192 @@
+use crate::try_clone::TryClone;
+use crate::vec::Vec;
+use alloc::alloc::Allocator;
+use alloc::collections::TryReserveError;
+use alloc::sync::Arc;
+use core::ops::Deref;
+
+/// A copy-on-write vec: it either shares a vec behind an [`Arc`] or owns one.
+///
+/// Reading never allocates. The first call to [`CowVec::make_mut`] on a shared vec
+/// clones it via [`TryClone`], so the allocation failure is reported there.
+pub enum CowVec<T, A: Allocator> {
+    Shared(Arc<Vec<T, A>>),
+    Owned(Vec<T, A>),
+}
+
+impl<T, A: Allocator> CowVec<T, A> {
+    #[inline]
+    pub fn is_shared(&self) -> bool {
+        matches!(self, CowVec::Shared(_))
+    }
+
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        matches!(self, CowVec::Owned(_))
+    }
+}
+
+impl<T, A: Allocator> CowVec<T, A>
+where
+    Vec<T, A>: TryClone<Error = TryReserveError>,
+{
+    /// Returns a mutable reference to the owned vec, taking ownership of the shared one
+    /// first. The shared vec is only cloned if there are other references to it.
+    /// On failure the `CowVec` is left sharing the original vec.
+    pub fn make_mut(&mut self) -> Result<&mut Vec<T, A>, TryReserveError>
+    where
+        A: Clone,
+    {
+        match self {
+            CowVec::Owned(vec) => Ok(vec),
+            CowVec::Shared(shared) => {
+                let owned = if let Some(vec) = Arc::get_mut(shared) {
+                    // The only reference: move the vec out, leaving an empty one that
+                    // doesn't allocate.
+                    let empty = Vec::new_in(vec.allocator().clone());
+                    core::mem::replace(vec, empty)
+                } else {
+                    shared.as_ref().try_clone()?
+                };
+                *self = CowVec::Owned(owned);
+                // Now owned, so this returns through the arm above.
+                self.make_mut()
+            }
+        }
+    }
+
+    /// Returns the owned vec. A shared vec is only cloned if there are other references to it.
+    pub fn into_owned(self) -> Result<Vec<T, A>, TryReserveError> {
+        match self {
+            CowVec::Shared(shared) => match Arc::try_unwrap(shared) {
+                Ok(vec) => Ok(vec),
+                Err(shared) => shared.as_ref().try_clone(),
+            },
+            CowVec::Owned(vec) => Ok(vec),
+        }
+    }
+}
+
+impl<T, A: Allocator> Deref for CowVec<T, A> {
+    type Target = Vec<T, A>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            CowVec::Shared(vec) => vec,
+            CowVec::Owned(vec) => vec,
+        }
+    }
+}
+
+impl<T, A: Allocator> From<Arc<Vec<T, A>>> for CowVec<T, A> {
+    fn from(vec: Arc<Vec<T, A>>) -> Self {
+        CowVec::Shared(vec)
+    }
+}
+
+impl<T, A: Allocator> From<Vec<T, A>> for CowVec<T, A> {
+    fn from(vec: Vec<T, A>) -> Self {
+        CowVec::Owned(vec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{AllowGlobalAllocGuard, NoGlobalAllocGuard};
+    use crate::testing::{CountingAllocator, WatermarkAllocator};
+
+    #[test]
+    fn test_shared_read() {
+        let ca = CountingAllocator::new();
+        // The `Arc` itself is allocated and freed globally, outside of the guard.
+        let shared = Arc::new(Vec::try_from_slice_in(&[1, 2, 3], ca.clone()).unwrap());
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let allocations = ca.allocations();
+
+        let cow = CowVec::from(shared.clone());
+        assert!(cow.is_shared());
+        assert_eq!(cow.as_slice(), &[1, 2, 3]);
+        assert_eq!(cow.len(), 3);
+        assert_eq!(ca.allocations(), allocations);
+        assert_eq!(Arc::strong_count(&shared), 2);
+    }
+
+    #[test]
+    fn test_make_mut() {
+        let ca = CountingAllocator::new();
+        // The `Arc` itself is allocated and freed globally, outside of the guard.
+        let shared = Arc::new(Vec::try_from_slice_in(&[1, 2, 3], ca.clone()).unwrap());
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let allocations = ca.allocations();
+
+        let mut cow = CowVec::from(shared.clone());
+        cow.make_mut().unwrap()[0] = 10;
+        assert!(cow.is_owned());
+        assert_eq!(ca.allocations(), allocations + 1);
+
+        // Already owned: no further copies.
+        cow.make_mut().unwrap()[1] = 20;
+        assert_eq!(ca.allocations(), allocations + 1);
+        assert_eq!(cow.as_slice(), &[10, 20, 3]);
+        assert_eq!(shared.as_slice(), &[1, 2, 3]);
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
+
+    #[test]
+    fn test_make_mut_unique() {
+        let ca = CountingAllocator::new();
+        let shared = Arc::new(Vec::try_from_slice_in(&[1, 2, 3], ca.clone()).unwrap());
+        let ptr = shared.as_ptr();
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let allocations = ca.allocations();
+
+        let mut cow = CowVec::from(shared);
+        {
+            // Dropping the `Arc` frees its global allocation.
+            let _allow_global_alloc_guard = AllowGlobalAllocGuard::new();
+            cow.make_mut().unwrap()[0] = 10;
+        }
+        assert!(cow.is_owned());
+        assert_eq!(cow.as_slice(), &[10, 2, 3]);
+        assert_eq!(cow.as_ptr(), ptr);
+        assert_eq!(ca.allocations(), allocations);
+    }
+
+    #[test]
+    fn test_make_mut_allocation_failure() {
+        let wma = WatermarkAllocator::new(16);
+        // The `Arc` itself is allocated and freed globally, outside of the guard.
+        let shared = Arc::new(Vec::try_from_slice_in(&[1u32, 2, 3], wma.clone()).unwrap());
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+
+        let mut cow = CowVec::from(shared.clone());
+        let _err: TryReserveError = cow.make_mut().unwrap_err();
+        assert!(cow.is_shared());
+        assert_eq!(cow.as_slice(), &[1, 2, 3]);
+        assert_eq!(wma.in_use(), 3 * size_of::<u32>());
+    }
+
+    #[test]
+    fn test_into_owned() {
+        let ca = CountingAllocator::new();
+        // The `Arc` itself is allocated and freed globally, outside of the guard.
+        let shared = Arc::new(Vec::try_from_slice_in(&[1, 2, 3], ca.clone()).unwrap());
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let allocations = ca.allocations();
+
+        // Another reference exists, so the vec is cloned.
+        let owned = CowVec::from(shared.clone()).into_owned().unwrap();
+        assert_eq!(owned.as_slice(), &[1, 2, 3]);
+        assert_eq!(ca.allocations(), allocations + 1);
+
+        // The last reference is unwrapped without cloning.
+        let ptr = shared.as_ptr();
+        let owned = {
+            let _allow_global_alloc_guard = AllowGlobalAllocGuard::new();
+            CowVec::from(shared).into_owned().unwrap()
+        };
+        assert_eq!(owned.as_ptr(), ptr);
+        assert_eq!(ca.allocations(), allocations + 1);
+    }
+}
@@ -0,0 +1,111 @@
+use alloc::alloc::Layout;
+use alloc::collections::{TryReserveError, TryReserveErrorKind};
+use core::fmt::{Display, Formatter};
+
+/// The broad cause of a [`TryReserveError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReserveErrorClass {
+    /// The requested capacity exceeded the maximum for the collection.
+    /// Retrying the same request can't succeed.
+    CapacityOverflow,
+
+    /// The allocator failed to satisfy the request.
+    AllocError,
+}
+
+/// Classifies `error` by its cause, e.g. to only retry on allocator failures.
+pub fn classify_reserve_error(error: &TryReserveError) -> ReserveErrorClass {
+    match error.kind() {
+        TryReserveErrorKind::CapacityOverflow => ReserveErrorClass::CapacityOverflow,
+        TryReserveErrorKind::AllocError { .. } => ReserveErrorClass::AllocError,
+    }
+}
+
+/// A [`TryReserveError`] together with the layout of the allocation that failed.
+///
+/// The layout is `None` when the failure is a capacity overflow, i.e. the
+/// requested size could not even be computed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AllocFailure {
+    error: TryReserveError,
+    layout: Option<Layout>,
+}
+
+impl AllocFailure {
+    /// The underlying error.
+    pub fn error(&self) -> &TryReserveError {
+        &self.error
+    }
+
+    /// The layout of the allocation request that failed, if there was one.
+    pub fn layout(&self) -> Option<Layout> {
+        self.layout
+    }
+
+    /// The number of bytes requested by the failed allocation, if there was one.
+    pub fn requested_bytes(&self) -> Option<usize> {
+        self.layout.map(|layout| layout.size())
+    }
+
+    /// Discards the layout, returning the underlying error.
+    pub fn into_inner(self) -> TryReserveError {
+        self.error
+    }
+}
+
+impl From<TryReserveError> for AllocFailure {
+    fn from(error: TryReserveError) -> Self {
+        let layout = match error.kind() {
+            TryReserveErrorKind::AllocError { layout, .. } => Some(layout),
+            TryReserveErrorKind::CapacityOverflow => None,
+        };
+        Self { error, layout }
+    }
+}
+
+impl From<AllocFailure> for TryReserveError {
+    fn from(failure: AllocFailure) -> Self {
+        failure.error
+    }
+}
+
+impl Display for AllocFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.layout {
+            Some(layout) => write!(
+                f,
+                "{} (requested {} bytes, align {})",
+                self.error,
+                layout.size(),
+                layout.align()
+            ),
+            None => Display::fmt(&self.error, f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{NoGlobalAllocGuard, WatermarkAllocator};
+    use crate::vec::Vec;
+
+    #[test]
+    fn test_classify_reserve_error() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec: Vec<u64, _> = Vec::new_in(wma);
+
+        let overflow = vec.reserve(usize::MAX).unwrap_err();
+        assert_eq!(
+            classify_reserve_error(&overflow),
+            ReserveErrorClass::CapacityOverflow
+        );
+
+        let alloc_error = vec.reserve(16).unwrap_err();
+        assert_eq!(
+            classify_reserve_error(&alloc_error),
+            ReserveErrorClass::AllocError
+        );
+    }
+}
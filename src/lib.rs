@@ -1,11 +1,17 @@
 #![cfg_attr(not(test), cfg_attr(feature = "no_std", no_std))]
 #![feature(allocator_api)]
+#![feature(slice_range)]
+#![feature(try_reserve_kind)]
 #![cfg_attr(not(doctest), doc = include_str!("../README.md"))]
 
 extern crate alloc;
 extern crate core;
 
+pub mod bounded_vec_deque;
 pub mod claim;
+pub mod cow_vec;
+pub mod error;
+pub mod pod;
 pub mod try_clone;
 pub mod vec;
 pub mod vec_deque;
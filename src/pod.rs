@@ -0,0 +1,31 @@
+mod private {
+    pub trait Sealed {}
+}
+
+/// A marker trait for plain-old-data types: they have no padding and every bit pattern
+/// is a valid value, so their memory can be safely viewed as bytes and written through.
+///
+/// This trait is sealed and only implemented for the primitive integer and float types.
+pub trait Pod: Copy + private::Sealed {}
+
+/// A marker trait for types whose all-zero bit pattern is a valid value (zero),
+/// so memory handed out zeroed by the allocator can be used as is.
+///
+/// This trait is sealed and only implemented for the primitive integer and float types.
+pub trait Zeroable: private::Sealed {}
+
+macro_rules! impl_pod_for {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+            impl Pod for $t {}
+            impl Zeroable for $t {}
+        )*
+    };
+}
+
+impl_pod_for! {
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize,
+    f32, f64
+}
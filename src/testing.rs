@@ -197,3 +197,105 @@ unsafe impl Allocator for WatermarkAllocator2 {
         self.0.deallocate(ptr, layout)
     }
 }
+
+#[derive(Default)]
+struct AllocCounts {
+    allocations: AtomicUsize,
+    grows: AtomicUsize,
+    shrinks: AtomicUsize,
+}
+
+/// An allocator that counts the calls made to it, delegating to the global allocator.
+/// This is used to assert how many times a collection (re)allocates.
+#[derive(Clone)]
+pub struct CountingAllocator {
+    counts: Option<Arc<AllocCounts>>,
+}
+
+impl Drop for CountingAllocator {
+    fn drop(&mut self) {
+        let counts = self.counts.take().unwrap();
+        let _g = AllowGlobalAllocGuard::new();
+        drop(counts);
+    }
+}
+
+impl CountingAllocator {
+    pub fn new() -> Self {
+        let counts = Some({
+            let _g = AllowGlobalAllocGuard::new();
+            AllocCounts::default().into()
+        });
+        Self { counts }
+    }
+
+    fn counts(&self) -> &AllocCounts {
+        self.counts.as_ref().unwrap()
+    }
+
+    pub fn allocations(&self) -> usize {
+        self.counts().allocations.load(Ordering::SeqCst)
+    }
+
+    pub fn grows(&self) -> usize {
+        self.counts().grows.load(Ordering::SeqCst)
+    }
+
+    pub fn shrinks(&self) -> usize {
+        self.counts().shrinks.load(Ordering::SeqCst)
+    }
+}
+
+impl Claim for CountingAllocator {}
+
+unsafe impl Allocator for CountingAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.counts().allocations.fetch_add(1, Ordering::SeqCst);
+        let _g = AllowGlobalAllocGuard::new();
+        Global.allocate(layout)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.counts().allocations.fetch_add(1, Ordering::SeqCst);
+        let _g = AllowGlobalAllocGuard::new();
+        Global.allocate_zeroed(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        let _g = AllowGlobalAllocGuard::new();
+        Global.deallocate(ptr, layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.counts().grows.fetch_add(1, Ordering::SeqCst);
+        let _g = AllowGlobalAllocGuard::new();
+        Global.grow(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.counts().grows.fetch_add(1, Ordering::SeqCst);
+        let _g = AllowGlobalAllocGuard::new();
+        Global.grow_zeroed(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.counts().shrinks.fetch_add(1, Ordering::SeqCst);
+        let _g = AllowGlobalAllocGuard::new();
+        Global.shrink(ptr, old_layout, new_layout)
+    }
+}
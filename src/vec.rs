@@ -1,14 +1,22 @@
 use crate::claim::Claim;
+use crate::error::AllocFailure;
+use crate::pod::{Pod, Zeroable};
 use crate::try_clone::TryClone;
-use alloc::alloc::Allocator;
-use alloc::collections::TryReserveError;
-use alloc::vec::Vec as InnerVec;
+use crate::vec_deque::VecDeque;
+use alloc::alloc::{Allocator, Layout};
+use alloc::collections::{TryReserveError, TryReserveErrorKind};
+use alloc::vec::{Drain, IntoIter, Vec as InnerVec};
+use core::cell::Cell;
 use core::fmt::Debug;
-use core::ops::{Deref, DerefMut, Index, IndexMut};
-use core::slice::SliceIndex;
+use core::hash::{Hash, Hasher};
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, DerefMut, Index, IndexMut, Range, RangeBounds};
+use core::ptr::NonNull;
+use core::slice::{ChunkBy, RSplit, SliceIndex, Split};
 
 pub struct Vec<T, A: Allocator> {
     inner: InnerVec<T, A>,
+    capacity_floor: usize,
 }
 
 impl<T, A: Allocator> Vec<T, A> {
@@ -16,6 +24,7 @@ impl<T, A: Allocator> Vec<T, A> {
     pub fn new_in(alloc: A) -> Self {
         Self {
             inner: InnerVec::new_in(alloc),
+            capacity_floor: 0,
         }
     }
 
@@ -29,36 +38,246 @@ impl<T, A: Allocator> Vec<T, A> {
     }
 
     #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
     pub fn reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
         self.inner.try_reserve(additional)
     }
 
+    /// Reserves capacity for exactly `additional` more elements, without the
+    /// amortized over-allocation of [`Vec::reserve`]. The allocator may still
+    /// return more memory than requested.
+    #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.inner.try_reserve_exact(additional)
+    }
+
+    /// Like [`Vec::reserve`], but the amortized growth is capped so that capacity is
+    /// grown to at most `max_over_alloc` elements beyond `len + additional`.
+    ///
+    /// This helps stay within tight allocator budgets where doubling would fail.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn reserve_amortized_capped(
+        &mut self,
+        additional: usize,
+        max_over_alloc: usize,
+    ) -> Result<(), TryReserveError> {
+        let len = self.len();
+        let capacity = self.capacity();
+        let required = len
+            .checked_add(additional)
+            .ok_or(TryReserveErrorKind::CapacityOverflow)?;
+        if required <= capacity {
+            return Ok(());
+        }
+        let amortized = capacity.saturating_mul(2).max(required);
+        let target = amortized.min(required.saturating_add(max_over_alloc));
+        self.inner.try_reserve_exact(target - len)
+    }
+
+    /// Sets a capacity floor that [`Vec::shrink_to_fit`] and [`Vec::shrink_to`] won't go
+    /// below, to avoid repeatedly releasing and re-acquiring memory for buffers that
+    /// oscillate in size. This doesn't allocate: the capacity may currently be lower.
+    ///
+    /// The floor is carried over by [`TryClone::try_clone`], but is reset to 0 when
+    /// converting to or from `alloc::vec::Vec` or a [`VecDeque`].
+    #[inline]
+    pub fn set_capacity_floor(&mut self, floor: usize) {
+        self.capacity_floor = floor;
+    }
+
+    #[inline]
+    pub fn capacity_floor(&self) -> usize {
+        self.capacity_floor
+    }
+
+    /// Shrinks the capacity as much as possible, but not below the length
+    /// or the floor set via [`Vec::set_capacity_floor`].
+    #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn shrink_to_fit(&mut self) -> Result<(), TryReserveError> {
+        self.shrink_to(0)
+    }
+
+    /// Shrinks the capacity to `min_capacity`, but not below the length or the floor
+    /// set via [`Vec::set_capacity_floor`]. Does nothing if the capacity is already lower.
+    ///
+    /// Unlike std, a failure to reallocate is reported rather than aborting,
+    /// in which case the vec is left unchanged.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn shrink_to(&mut self, min_capacity: usize) -> Result<(), TryReserveError> {
+        let capacity = self.capacity();
+        let target = min_capacity.max(self.len()).max(self.capacity_floor);
+        if size_of::<T>() == 0 || target >= capacity {
+            return Ok(());
+        }
+
+        // SAFETY: the vec owns an allocation of `capacity` elements, and thus a valid layout.
+        // The raw parts are only taken over once the allocator has succeeded, so on failure
+        // `self.inner` is left untouched.
+        unsafe {
+            let old_layout =
+                Layout::from_size_align_unchecked(size_of::<T>() * capacity, align_of::<T>());
+            let new_layout =
+                Layout::from_size_align_unchecked(size_of::<T>() * target, align_of::<T>());
+            let old_ptr = NonNull::new_unchecked(self.inner.as_mut_ptr().cast::<u8>());
+            let new_ptr = if target == 0 {
+                self.inner.allocator().deallocate(old_ptr, old_layout);
+                NonNull::<T>::dangling()
+            } else {
+                self.inner
+                    .allocator()
+                    .shrink(old_ptr, old_layout, new_layout)
+                    .map_err(|_| alloc_error(new_layout))?
+                    .cast::<T>()
+            };
+            self.replace_buffer(new_ptr, target);
+        }
+        Ok(())
+    }
+
+    /// Swaps in a buffer of `capacity` elements that this vec's allocator handed out
+    /// in place of the current one (which it must have already released), keeping the length.
+    unsafe fn replace_buffer(&mut self, ptr: NonNull<T>, capacity: usize) {
+        let len = self.len();
+        let old = ManuallyDrop::new(core::ptr::read(&self.inner));
+        let alloc = core::ptr::read(old.allocator());
+        core::ptr::write(
+            &mut self.inner,
+            InnerVec::from_raw_parts_in(ptr.as_ptr(), len, capacity, alloc),
+        );
+    }
+
     #[inline]
     pub fn with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
         Ok(Self {
             inner: InnerVec::try_with_capacity_in(capacity, alloc)?,
+            capacity_floor: 0,
         })
     }
 
+    /// Creates a vec from an iterator of known length, allocating exactly once.
+    ///
+    /// Should the iterator yield more items than it reported, the vec grows as needed.
+    pub fn try_from_exact_iter_in<I>(iter: I, alloc: A) -> Result<Self, TryReserveError>
+    where
+        I: IntoIterator<Item = T, IntoIter: ExactSizeIterator>,
+    {
+        let iter = iter.into_iter();
+        let mut vec = Self::with_capacity_in(iter.len(), alloc)?;
+        for value in iter {
+            if vec.len() < vec.capacity() {
+                // SAFETY: we just checked there's spare capacity.
+                unsafe { vec.push_unchecked(value) };
+            } else {
+                vec.push(value)?;
+            }
+        }
+        Ok(vec)
+    }
+
+    /// Creates a vec from an iterator, pre-reserving its `size_hint` lower bound.
+    ///
+    /// This is the fallible counterpart to `FromIterator`. Should any reservation
+    /// fail, the partially built vec is dropped and only the error is returned.
+    pub fn try_from_iter_in<I>(iter: I, alloc: A) -> Result<Self, TryReserveError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut vec = Self::new_in(alloc);
+        vec.extend(iter)?;
+        Ok(vec)
+    }
+
     #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
     pub fn push(&mut self, value: T) -> Result<(), TryReserveError> {
         self.reserve(1)?;
         // SAFETY: we just reserved space for one more element.
         unsafe {
-            self.unsafe_push(value);
+            self.push_unchecked(value);
         }
         Ok(())
     }
 
+    /// Returns the spare capacity as a slice of possibly uninitialized elements,
+    /// e.g. to fill it in place before committing the length via [`Vec::set_len`].
+    #[inline]
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        self.inner.spare_capacity_mut()
+    }
+
+    /// Sets the length of the vec without dropping or initializing any elements.
+    ///
+    /// # Safety
+    ///
+    /// - `new_len` must be less than or equal to [`Vec::capacity`].
+    /// - The elements at `old_len..new_len` must be initialized, e.g. written
+    ///   through [`Vec::spare_capacity_mut`].
+    /// - When shrinking, the elements at `new_len..old_len` are not dropped;
+    ///   the caller becomes responsible for them.
+    #[inline]
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        self.inner.set_len(new_len)
+    }
+
+    /// Appends `value` without checking for spare capacity.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `self.len() < self.capacity()`, e.g. by having
+    /// previously called [`Vec::reserve`] for enough elements.
     #[inline]
-    unsafe fn unsafe_push(&mut self, value: T) {
+    pub unsafe fn push_unchecked(&mut self, value: T) {
         let len = self.inner.len();
         let end = self.inner.as_mut_ptr().add(len);
         core::ptr::write(end, value);
         self.inner.set_len(len + 1)
     }
 
+    /// Inserts `value` at `index`, shifting the elements after it.
+    /// If reserving space fails, the vec is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the vec's length.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn insert(&mut self, index: usize, value: T) -> Result<(), TryReserveError> {
+        assert!(index <= self.len(), "insertion index out of bounds");
+        self.reserve(1)?;
+        self.inner.insert(index, value);
+        Ok(())
+    }
+
+    /// Removes and returns the element at `index`, shifting the elements after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn remove(&mut self, index: usize) -> T {
+        self.inner.remove(index)
+    }
+
+    /// Removes and returns the element at `index`, replacing it with the last element.
+    /// This is O(1) but doesn't preserve the order of the elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        self.inner.swap_remove(index)
+    }
+
+    /// Appends all the items of `iter`.
+    ///
+    /// This is all-or-nothing: on failure the vec is truncated back to its original
+    /// length, dropping any items appended by this call. Items already pulled from
+    /// the iterator are not given back, so any side effects of `next()` remain.
+    #[must_use = "ignoring the error hides an allocation failure"]
     pub fn extend(&mut self, iter: impl IntoIterator<Item = T>) -> Result<(), TryReserveError> {
+        let len = self.len();
         let mut iter = iter.into_iter();
         let (lower_bound, _) = iter.size_hint();
 
@@ -69,13 +288,55 @@ impl<T, A: Allocator> Vec<T, A> {
                 return Ok(());
             };
             unsafe {
-                self.unsafe_push(value);
+                self.push_unchecked(value);
             }
         }
 
         // Dynamically append the rest
         for value in iter {
-            self.push(value)?;
+            if let Err(error) = self.push(value) {
+                self.inner.truncate(len);
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends `f(x)` for each element `x` of `src`, reserving `src.len()` elements once.
+    /// If reserving fails, the vec is left unchanged.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn extend_from_slice_map<U, F>(
+        &mut self,
+        src: &[U],
+        mut f: F,
+    ) -> Result<(), TryReserveError>
+    where
+        F: FnMut(&U) -> T,
+    {
+        self.reserve(src.len())?;
+        for item in src {
+            // Space was reserved above. Pushing one at a time keeps the length
+            // correct should `f` panic.
+            unsafe {
+                self.push_unchecked(f(item));
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends the `Ok` values of `iter`, stopping at the first `Err`.
+    /// Values appended before the error (user or allocation) are kept.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn try_extend_from_results<I, E>(&mut self, iter: I) -> Result<(), E>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+        E: From<TryReserveError>,
+    {
+        let iter = iter.into_iter();
+        let (lower_bound, _) = iter.size_hint();
+        self.reserve(lower_bound)?;
+        for item in iter {
+            self.push(item?)?;
         }
         Ok(())
     }
@@ -115,6 +376,19 @@ impl<T, A: Allocator> Vec<T, A> {
         self.inner.as_mut_ptr()
     }
 
+    /// Returns the half-open range of pointers spanning the elements, e.g. to pass
+    /// `begin`/`end` pointers over FFI. The end pointer is one past the last element.
+    #[inline]
+    pub fn as_ptr_range(&self) -> Range<*const T> {
+        self.inner.as_ptr_range()
+    }
+
+    /// Like [`Vec::as_ptr_range`], but for mutable access.
+    #[inline]
+    pub fn as_mut_ptr_range(&mut self) -> Range<*mut T> {
+        self.inner.as_mut_ptr_range()
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         self.inner.clear();
@@ -125,7 +399,98 @@ impl<T, A: Allocator> Vec<T, A> {
         self.inner.truncate(new_len);
     }
 
+    /// Keeps only the elements for which `f` returns `true`, preserving their order.
+    /// Nothing is allocated.
+    #[inline]
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, f: F) {
+        self.inner.retain(f);
+    }
+
+    /// Like [`Vec::retain`], but `f` may also mutate the elements.
+    #[inline]
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, f: F) {
+        self.inner.retain_mut(f);
+    }
+
+    /// Removes the elements matching `should_clear`, keeping the capacity.
+    /// Returns the number of elements removed.
+    #[inline]
+    pub fn clear_where<F: FnMut(&T) -> bool>(&mut self, mut should_clear: F) -> usize {
+        let len = self.len();
+        self.inner.retain(|x| !should_clear(x));
+        len - self.len()
+    }
+
+    /// Removes all the elements front to back, passing each to `f`.
+    /// The capacity is kept and nothing is allocated.
+    #[inline]
+    pub fn clear_each(&mut self, f: impl FnMut(T)) {
+        self.inner.drain(..).for_each(f);
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, filling each removed slot
+    /// with the last element instead of shifting the rest down.
+    ///
+    /// This does **not** preserve the relative order of the kept elements.
+    /// Nothing is allocated.
+    pub fn retain_unordered<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let mut index = 0;
+        while index < self.len() {
+            if f(&self.inner[index]) {
+                index += 1;
+            } else {
+                // The swapped-in element hasn't been checked yet, so don't advance.
+                self.swap_remove(index);
+            }
+        }
+    }
+
+    /// Truncates the vec to `new_len`, passing each removed element to `f` in order.
+    /// Does nothing if `new_len >= len`. The capacity is kept and nothing is allocated.
+    #[inline]
+    pub fn truncate_each(&mut self, new_len: usize, f: impl FnMut(T)) {
+        if new_len < self.len() {
+            self.inner.drain(new_len..).for_each(f);
+        }
+    }
+
+    /// Removes the elements in `range`, returning them as an iterator. The elements after
+    /// the range are shifted down once the iterator is dropped. Nothing is allocated.
+    ///
+    /// If the iterator is leaked (e.g. with `mem::forget`), the vec is left truncated to the
+    /// start of the range and the remaining elements are leaked, but it stays valid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or its start is greater than its end.
+    #[inline]
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, A> {
+        self.inner.drain(range)
+    }
+
+    /// Removes the elements from `at` onwards, returning them as an iterator.
+    /// The removed elements are not re-allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is greater than the vec's length.
+    #[inline]
+    pub fn drain_tail(&mut self, at: usize) -> Drain<'_, T, A> {
+        self.inner.drain(at..)
+    }
+
+    /// Keeps only the last `n` elements, dropping the ones before them and
+    /// shifting the rest to the front. Does nothing if `n >= len`.
+    #[inline]
+    pub fn keep_last(&mut self, n: usize) {
+        let len = self.len();
+        if n < len {
+            self.inner.drain(..len - n);
+        }
+    }
+
     #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
     pub fn resize_with<F: FnMut() -> T>(
         &mut self,
         new_len: usize,
@@ -147,28 +512,473 @@ impl<T, A: Allocator> Vec<T, A> {
         Ok(())
     }
 
-    pub(crate) fn into_inner(self) -> InnerVec<T, A> {
+    /// Replaces the elements in `range` with those from `replace_with`, returning the
+    /// removed elements in a new vec allocated from a clone of this vec's allocator.
+    ///
+    /// The replacement is buffered before any change is made, so on error `self` is
+    /// left untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or its start is greater than its end.
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> Result<Vec<T, A>, TryReserveError>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+        A: Claim,
+    {
+        let range = core::slice::range(range, ..self.len());
+        let mut replacement = Vec::new_in(self.allocator().clone());
+        replacement.extend(replace_with)?;
+        let mut removed = Vec::with_capacity_in(range.len(), self.allocator().clone())?;
+        self.reserve(replacement.len().saturating_sub(range.len()))?;
+
+        // Neither of these can allocate: `removed` has exactly enough capacity for the
+        // spliced out range and `self` has room for the extra replacement elements.
+        removed
+            .inner
+            .extend(self.inner.splice(range, replacement.into_inner()));
+        Ok(removed)
+    }
+
+    /// Sorts the vec (without allocating) and removes duplicates, leaving a sorted set.
+    #[inline]
+    pub fn sort_unstable_dedup(&mut self)
+    where
+        T: Ord,
+    {
+        self.inner.sort_unstable();
+        self.inner.dedup();
+    }
+
+    /// Inserts the elements of `iter` at `index`, shifting the elements after it.
+    /// No `Claim` bound is needed since the elements are moved in.
+    ///
+    /// The elements are appended first (growing as needed should the iterator's size hint
+    /// be off) and then rotated into place. On failure the vec is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the vec's length.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn insert_iter<I: IntoIterator<Item = T>>(
+        &mut self,
+        index: usize,
+        iter: I,
+    ) -> Result<(), TryReserveError> {
+        let len = self.len();
+        assert!(index <= len, "insertion index out of bounds");
+        if let Err(error) = self.extend(iter) {
+            self.inner.truncate(len);
+            return Err(error);
+        }
+        let inserted = self.len() - len;
+        self.inner[index..].rotate_right(inserted);
+        Ok(())
+    }
+
+    /// Inserts `value` into a sorted vec unless an equal element is already present.
+    ///
+    /// Returns `Ok(Ok(index))` with the index of the existing element, in which case
+    /// nothing is allocated, or `Ok(Err(index))` with the index `value` was inserted at.
+    pub fn insert_sorted_unique(
+        &mut self,
+        value: T,
+    ) -> Result<Result<usize, usize>, TryReserveError>
+    where
+        T: Ord,
+    {
+        match self.inner.binary_search(&value) {
+            Ok(index) => Ok(Ok(index)),
+            Err(index) => {
+                self.reserve(1)?;
+                self.inner.insert(index, value);
+                Ok(Err(index))
+            }
+        }
+    }
+
+    /// Moves the elements in `range` out of `src` and appends them to `self`.
+    /// If reserving space fails, `src` is left untouched.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn append_range_from<R: RangeBounds<usize>>(
+        &mut self,
+        src: &mut Vec<T, A>,
+        range: R,
+    ) -> Result<(), TryReserveError> {
+        let range = core::slice::range(range, ..src.len());
+        self.reserve(range.len())?;
+        self.inner.extend(src.inner.drain(range));
+        Ok(())
+    }
+
+    /// Moves all the elements of `other` to the back of the vec, in logical order,
+    /// leaving `other` empty. If reserving space fails, `other` is left untouched.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn append_deque(&mut self, other: &mut VecDeque<T, A>) -> Result<(), TryReserveError> {
+        self.reserve(other.len())?;
+        self.inner.extend(other.drain(..));
+        Ok(())
+    }
+
+    /// Folds the elements into an accumulator whose updates may allocate,
+    /// stopping at the first allocation failure.
+    #[inline]
+    pub fn try_fold<B, F>(&self, init: B, f: F) -> Result<B, TryReserveError>
+    where
+        F: FnMut(B, &T) -> Result<B, TryReserveError>,
+    {
+        self.inner.iter().try_fold(init, f)
+    }
+
+    /// Returns the element for which `f` returns the largest key, or `None` if the vec
+    /// is empty. If several elements are equally maximal, the last one is returned.
+    #[inline]
+    pub fn max_by_key<B: Ord, F: FnMut(&T) -> B>(&self, mut f: F) -> Option<&T> {
+        self.inner.iter().max_by_key(|x| f(x))
+    }
+
+    /// Returns the element for which `f` returns the smallest key, or `None` if the vec
+    /// is empty. If several elements are equally minimal, the first one is returned.
+    #[inline]
+    pub fn min_by_key<B: Ord, F: FnMut(&T) -> B>(&self, mut f: F) -> Option<&T> {
+        self.inner.iter().min_by_key(|x| f(x))
+    }
+
+    /// Overwrites each element with `f(index)`, keeping the length. Nothing is allocated.
+    #[inline]
+    pub fn fill_indexed(&mut self, mut f: impl FnMut(usize) -> T) {
+        for (index, slot) in self.inner.iter_mut().enumerate() {
+            *slot = f(index);
+        }
+    }
+
+    /// Calls `f` on each element in order, stopping at the first allocation failure.
+    #[inline]
+    pub fn try_for_each<F>(&self, f: F) -> Result<(), TryReserveError>
+    where
+        F: FnMut(&T) -> Result<(), TryReserveError>,
+    {
+        self.inner.iter().try_for_each(f)
+    }
+
+    /// Removes the elements matching `pred` and returns them in a new vec allocated from
+    /// a clone of this vec's allocator. Both vecs keep the elements' relative order.
+    ///
+    /// `pred` is called twice per element (once to size the output, once to extract),
+    /// so it should be deterministic. On allocation failure `self` is left untouched.
+    pub fn take_matching<F: FnMut(&T) -> bool>(
+        &mut self,
+        mut pred: F,
+    ) -> Result<Vec<T, A>, TryReserveError>
+    where
+        A: Claim,
+    {
+        let count = self.inner.iter().filter(|x| pred(x)).count();
+        let mut taken = Vec::with_capacity_in(count, self.allocator().clone())?;
+        let mut remaining = count;
+        let extracted = self.inner.extract_if(.., |x| {
+            let take = remaining > 0 && pred(x);
+            remaining -= usize::from(take);
+            take
+        });
+        for value in extracted {
+            // SAFETY: `taken` has capacity for `count` elements and we extract at most `count`.
+            unsafe { taken.push_unchecked(value) };
+        }
+        Ok(taken)
+    }
+
+    /// Returns the number of maximal runs of equal adjacent elements,
+    /// e.g. 3 for `[1, 1, 2, 3, 3, 3]` and 0 for an empty vec.
+    pub fn count_runs(&self) -> usize
+    where
+        T: PartialEq,
+    {
+        if self.is_empty() {
+            return 0;
+        }
+        1 + self
+            .inner
+            .windows(2)
+            .filter(|pair| pair[0] != pair[1])
+            .count()
+    }
+
+    /// Iterates over the subslices separated by elements matching `pred`.
+    #[inline]
+    pub fn split<F: FnMut(&T) -> bool>(&self, pred: F) -> Split<'_, T, F> {
+        self.inner.split(pred)
+    }
+
+    /// Like [`Vec::split`], but starting from the end.
+    #[inline]
+    pub fn rsplit<F: FnMut(&T) -> bool>(&self, pred: F) -> RSplit<'_, T, F> {
+        self.inner.rsplit(pred)
+    }
+
+    /// Iterates over the runs of successive elements for which `pred` holds pairwise.
+    #[inline]
+    pub fn chunk_by<F: FnMut(&T, &T) -> bool>(&self, pred: F) -> ChunkBy<'_, T, F> {
+        self.inner.chunk_by(pred)
+    }
+
+    /// Iterates over the runs of consecutive elements that share the same key.
+    #[inline]
+    pub fn group_runs_by_key<K: PartialEq, F: FnMut(&T) -> K>(
+        &self,
+        key: F,
+    ) -> GroupRuns<'_, T, F> {
+        GroupRuns {
+            slice: self.inner.as_slice(),
+            key,
+        }
+    }
+
+    /// Merges two sorted vecs into a single sorted vec allocated from `alloc`,
+    /// reserving space for all the elements once. Equal elements from `self` come first.
+    pub fn try_merge_sorted(self, other: Self, alloc: A) -> Result<Self, TryReserveError>
+    where
+        T: Ord,
+    {
+        let total_len = self
+            .len()
+            .checked_add(other.len())
+            .ok_or(TryReserveErrorKind::CapacityOverflow)?;
+        let mut merged = Self::with_capacity_in(total_len, alloc)?;
+        let mut left = self.inner.into_iter().peekable();
+        let mut right = other.inner.into_iter().peekable();
+        loop {
+            let next = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) if r < l => right.next(),
+                (Some(_), _) => left.next(),
+                (None, _) => right.next(),
+            };
+            let Some(item) = next else {
+                break;
+            };
+            // SAFETY: we reserved space for all the elements up front.
+            unsafe { merged.push_unchecked(item) };
+        }
+        Ok(merged)
+    }
+
+    /// Views the elements as a slice of cells, so that they can be mutated through
+    /// shared references, e.g. while other elements are borrowed. Nothing is allocated.
+    ///
+    /// This takes `&mut self`: handing out cells from `&self` would let callers mutate
+    /// elements that other shared borrows of the vec expect to be frozen.
+    #[inline]
+    pub fn as_slice_of_cells(&mut self) -> &[Cell<T>] {
+        Cell::from_mut(self.inner.as_mut_slice()).as_slice_of_cells()
+    }
+
+    /// Swaps all the elements with those of `other`, in place and without allocating.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` has a different length.
+    #[inline]
+    pub fn swap_with_slice(&mut self, other: &mut [T]) {
+        self.inner.as_mut_slice().swap_with_slice(other);
+    }
+
+    /// Unwraps the underlying `alloc::vec::Vec`, keeping the buffer and allocator.
+    /// The capacity floor set via [`Vec::set_capacity_floor`] is discarded.
+    #[inline]
+    pub fn into_inner(self) -> InnerVec<T, A> {
         self.inner
     }
 }
 
+impl<T: Zeroable, A: Allocator> Vec<T, A> {
+    /// Appends `additional` zeroed elements, growing the capacity exactly if needed.
+    ///
+    /// New memory is requested already zeroed from the allocator (via `allocate_zeroed`
+    /// or `grow_zeroed`), which for large buffers can avoid a separate pass to clear them.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn try_grow_zeroed(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let len = self.len();
+        let capacity = self.capacity();
+        let new_len = len
+            .checked_add(additional)
+            .ok_or(TryReserveErrorKind::CapacityOverflow)?;
+        // Any spare capacity we already own is uninitialized and needs clearing by hand.
+        let mut to_clear = additional;
+        if new_len > capacity {
+            let new_layout =
+                Layout::array::<T>(new_len).map_err(|_| TryReserveErrorKind::CapacityOverflow)?;
+            // SAFETY: a non-zero capacity means the vec owns an allocation with a valid layout.
+            // The buffer is only replaced once the allocator has succeeded.
+            unsafe {
+                let new_ptr = if capacity == 0 {
+                    self.inner.allocator().allocate_zeroed(new_layout)
+                } else {
+                    let old_layout = Layout::from_size_align_unchecked(
+                        size_of::<T>() * capacity,
+                        align_of::<T>(),
+                    );
+                    let old_ptr = NonNull::new_unchecked(self.inner.as_mut_ptr().cast::<u8>());
+                    self.inner
+                        .allocator()
+                        .grow_zeroed(old_ptr, old_layout, new_layout)
+                }
+                .map_err(|_| alloc_error(new_layout))?;
+                self.replace_buffer(new_ptr.cast(), new_len);
+            }
+            to_clear = capacity - len;
+        }
+        // SAFETY: `len..new_len` is within capacity, and all-zero bytes are a valid `T`.
+        unsafe {
+            core::ptr::write_bytes(self.inner.as_mut_ptr().add(len), 0, to_clear);
+            self.inner.set_len(new_len);
+        }
+        Ok(())
+    }
+}
+
+impl<T: Pod, A: Allocator> Vec<T, A> {
+    /// Views the elements as raw bytes, in the platform's native endianness.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        let slice = self.inner.as_slice();
+        // SAFETY: `Pod` types have no padding, so every byte is initialized.
+        unsafe { core::slice::from_raw_parts(slice.as_ptr().cast(), size_of_val(slice)) }
+    }
+
+    /// Views the elements as mutable raw bytes, in the platform's native endianness.
+    #[inline]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        let slice = self.inner.as_mut_slice();
+        // SAFETY: `Pod` types have no padding and any bit pattern is a valid value.
+        unsafe { core::slice::from_raw_parts_mut(slice.as_mut_ptr().cast(), size_of_val(slice)) }
+    }
+}
+
+impl<T, A: Allocator> Vec<Vec<T, A>, A> {
+    /// Moves the elements of all the inner vecs into a single vec allocated from `alloc`,
+    /// reserving the total length once.
+    pub fn try_into_flattened(self, alloc: A) -> Result<Vec<T, A>, TryReserveError> {
+        let total_len = self
+            .iter()
+            .try_fold(0usize, |total, vec| total.checked_add(vec.len()))
+            .ok_or(TryReserveErrorKind::CapacityOverflow)?;
+        let mut flattened = Vec::with_capacity_in(total_len, alloc)?;
+        for vec in self.into_inner() {
+            // This won't allocate: we reserved space for all the elements up front.
+            flattened.inner.append(&mut vec.into_inner());
+        }
+        Ok(flattened)
+    }
+}
+
+fn alloc_error(layout: Layout) -> TryReserveError {
+    TryReserveErrorKind::AllocError {
+        layout,
+        non_exhaustive: (),
+    }
+    .into()
+}
+
+/// An iterator over runs of consecutive elements with equal keys.
+///
+/// Created by [`Vec::group_runs_by_key`].
+pub struct GroupRuns<'a, T, F> {
+    slice: &'a [T],
+    key: F,
+}
+
+impl<'a, T, K: PartialEq, F: FnMut(&T) -> K> Iterator for GroupRuns<'a, T, F> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first_key = (self.key)(self.slice.first()?);
+        let mut end = 1;
+        while end < self.slice.len() && (self.key)(&self.slice[end]) == first_key {
+            end += 1;
+        }
+        let (run, rest) = self.slice.split_at(end);
+        self.slice = rest;
+        Some(run)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.slice.len();
+        (usize::from(len > 0), Some(len))
+    }
+}
+
 impl<T: Claim, A: Allocator> Vec<T, A> {
+    /// Creates a vec holding clones of the elements of `slice`, allocating exactly
+    /// `slice.len()` elements once.
+    #[inline]
+    pub fn try_from_slice_in(slice: &[T], alloc: A) -> Result<Self, TryReserveError> {
+        let mut vec = Self::with_capacity_in(slice.len(), alloc)?;
+        vec.extend_from_slice(slice)?;
+        Ok(vec)
+    }
+
+    /// Returns a new vec in `alloc` holding the last occurrence of each distinct value,
+    /// in their original order, e.g. `[2, 1, 3]` for `[1, 2, 1, 3]`.
+    ///
+    /// This runs in O(n log n): the indices of the elements are sorted by value in a
+    /// scratch vec, also allocated in `alloc`, to find the last index of each value.
+    /// The result is then allocated exactly once.
+    pub fn try_dedup_total_last<A2>(&self, alloc: A2) -> Result<Vec<T, A2>, TryReserveError>
+    where
+        T: Ord,
+        A2: Allocator + Claim,
+    {
+        let len = self.len();
+        let mut order = Vec::with_capacity_in(len, alloc.clone())?;
+        order.extend(0..len)?;
+        // Ties are broken by index, so each run of equal values ends with its last occurrence.
+        order.sort_unstable_by(|&a, &b| self.inner[a].cmp(&self.inner[b]).then(a.cmp(&b)));
+
+        // Compact the last index of each run to the front. `kept <= i`, so this never
+        // overwrites an index that is yet to be visited.
+        let mut kept = 0;
+        for i in 0..len {
+            let index = order[i];
+            let is_last = order
+                .get(i + 1)
+                .is_none_or(|&next| self.inner[next] != self.inner[index]);
+            if is_last {
+                order[kept] = index;
+                kept += 1;
+            }
+        }
+        order.truncate(kept);
+        order.sort_unstable();
+
+        let mut deduped = Vec::with_capacity_in(kept, alloc)?;
+        for &index in order.iter() {
+            // SAFETY: we reserved space for all the kept elements up front.
+            unsafe { deduped.push_unchecked(self.inner[index].clone()) };
+        }
+        Ok(deduped)
+    }
+
     #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
     pub fn extend_from_slice(&mut self, slice: &[T]) -> Result<(), TryReserveError> {
         self.reserve(slice.len())?;
 
         // Yes, we re-evaluate the capacity by delegating to the inner Vec,
-        // but we also gain the optimizations available via specific implementations
-        // for anything that supports the `Copy` trait.
+        // but since `T: Claim`, none of the clones can panic.
+        // For `Copy` types this is a single memcpy.
         self.inner.extend_from_slice(slice);
         Ok(())
     }
 
     #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
     pub fn extend_with(&mut self, additional: usize, value: T) -> Result<(), TryReserveError> {
-        self.reserve(additional)?;
         let len = self.inner.len();
-        let new_len = len + additional;
+        let new_len = len
+            .checked_add(additional)
+            .ok_or(TryReserveErrorKind::CapacityOverflow)?;
+        self.reserve(additional)?;
         for index in len..new_len {
             unsafe {
                 let end = self.inner.as_mut_ptr().add(index);
@@ -180,6 +990,7 @@ impl<T: Claim, A: Allocator> Vec<T, A> {
     }
 
     #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
     pub fn resize(&mut self, new_len: usize, value: T) -> Result<(), TryReserveError> {
         let len = self.len();
         if new_len > len {
@@ -189,6 +1000,35 @@ impl<T: Claim, A: Allocator> Vec<T, A> {
         }
         Ok(())
     }
+
+    /// Sets the length to exactly `len`, appending clones of `fill` or truncating as needed.
+    ///
+    /// This is idempotent: if the length already matches, it returns immediately
+    /// without touching the elements or allocating.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn ensure_len(&mut self, len: usize, fill: T) -> Result<(), TryReserveError> {
+        if self.len() == len {
+            return Ok(());
+        }
+        self.resize(len, fill)
+    }
+
+    /// Discards the first `n` elements, shifts the rest to the front and fills the
+    /// vacated tail with `fill`. The length is unchanged and nothing is allocated.
+    pub fn shift_left(&mut self, n: usize, fill: T) {
+        let len = self.len();
+        let n = n.min(len);
+        self.inner.rotate_left(n);
+        self.inner[len - n..].fill(fill);
+    }
+
+    /// Discards the last `n` elements, shifts the rest to the back and fills the
+    /// vacated head with `fill`. The length is unchanged and nothing is allocated.
+    pub fn shift_right(&mut self, n: usize, fill: T) {
+        let n = n.min(self.len());
+        self.inner.rotate_right(n);
+        self.inner[..n].fill(fill);
+    }
 }
 
 impl<T: Claim, A: Allocator + Claim> TryClone for Vec<T, A> {
@@ -197,10 +1037,69 @@ impl<T: Claim, A: Allocator + Claim> TryClone for Vec<T, A> {
     fn try_clone(&self) -> Result<Self, Self::Error> {
         let mut cloned = Self::with_capacity_in(self.len(), self.allocator().clone())?;
         cloned.extend_from_slice(self.inner.as_slice())?;
+        cloned.capacity_floor = self.capacity_floor;
+        Ok(cloned)
+    }
+}
+
+impl<T: Claim, A: Allocator + Claim> Vec<T, A> {
+    /// Like [`TryClone::try_clone`], but on failure reports the layout of the
+    /// allocation that could not be satisfied.
+    pub fn try_clone_diagnostic(&self) -> Result<Self, AllocFailure> {
+        self.try_clone().map_err(AllocFailure::from)
+    }
+}
+
+/// Nested checked vecs can't be `Claim`, so they are cloned element by element.
+/// If cloning an element fails, the elements cloned so far are dropped.
+impl<T, A1, A2> TryClone for Vec<Vec<T, A1>, A2>
+where
+    A1: Allocator,
+    A2: Allocator + Claim,
+    Vec<T, A1>: TryClone<Error = TryReserveError>,
+{
+    type Error = TryReserveError;
+
+    fn try_clone(&self) -> Result<Self, Self::Error> {
+        let mut cloned = Self::with_capacity_in(self.len(), self.allocator().clone())?;
+        for vec in self.iter() {
+            let vec = vec.try_clone()?;
+            // SAFETY: we reserved space for all the elements up front.
+            unsafe { cloned.push_unchecked(vec) };
+        }
+        cloned.capacity_floor = self.capacity_floor;
         Ok(cloned)
     }
 }
 
+#[cfg(feature = "num-traits")]
+impl<T: Copy, A: Allocator> Vec<T, A> {
+    /// Returns the sum of the elements, e.g. `0` for an empty vec of integers.
+    #[inline]
+    pub fn sum_copied(&self) -> T
+    where
+        T: core::iter::Sum,
+    {
+        self.inner.iter().copied().sum()
+    }
+
+    /// Returns the arithmetic mean of the elements, computed in `f64`.
+    /// Returns `NaN` for an empty vec.
+    pub fn mean(&self) -> f64
+    where
+        T: num_traits::AsPrimitive<f64>,
+    {
+        let sum: f64 = self.inner.iter().map(|x| x.as_()).sum();
+        sum / self.len() as f64
+    }
+}
+
+impl<T, A: Allocator + Default> Default for Vec<T, A> {
+    fn default() -> Self {
+        Self::new_in(A::default())
+    }
+}
+
 impl<T, I: SliceIndex<[T]>, A: Allocator> Index<I> for Vec<T, A> {
     type Output = I::Output;
 
@@ -266,39 +1165,139 @@ __impl_slice_eq1! { [A: Allocator, const N: usize] [T; N], Vec<U, A> }
 __impl_slice_eq1! { [A: Allocator, const N: usize] Vec<T, A>, &[U; N] }
 __impl_slice_eq1! { [A: Allocator, const N: usize] &[T; N], Vec<U, A> }
 
-impl<T, A: Allocator> AsRef<Vec<T, A>> for Vec<T, A> {
-    fn as_ref(&self) -> &Vec<T, A> {
-        self
+impl<T: Eq, A: Allocator> Eq for Vec<T, A> {}
+
+/// Hashes the elements only, ignoring the allocator, so that vecs comparing equal
+/// across allocators also hash equally.
+impl<T: Hash, A: Allocator> Hash for Vec<T, A> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner.as_slice().hash(state)
     }
 }
 
-impl<T, A: Allocator> AsMut<Vec<T, A>> for Vec<T, A> {
-    fn as_mut(&mut self) -> &mut Vec<T, A> {
-        self
+/// Wraps an existing `alloc::vec::Vec` without reallocating. The capacity floor starts at 0.
+impl<T, A: Allocator> From<InnerVec<T, A>> for Vec<T, A> {
+    #[inline]
+    fn from(inner: InnerVec<T, A>) -> Self {
+        Self {
+            inner,
+            capacity_floor: 0,
+        }
     }
 }
 
-impl<T, A: Allocator> AsRef<[T]> for Vec<T, A> {
-    fn as_ref(&self) -> &[T] {
-        self
+impl<T, A: Allocator> IntoIterator for Vec<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    /// Consumes the vec. The buffer is released through `A` once the iterator is dropped.
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
     }
 }
 
-impl<T, A: Allocator> AsMut<[T]> for Vec<T, A> {
-    fn as_mut(&mut self) -> &mut [T] {
+impl<'a, T, A: Allocator> IntoIterator for &'a Vec<T, A> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a mut Vec<T, A> {
+    type Item = &'a mut T;
+    type IntoIter = core::slice::IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, A: Allocator> AsRef<Vec<T, A>> for Vec<T, A> {
+    fn as_ref(&self) -> &Vec<T, A> {
+        self
+    }
+}
+
+impl<T, A: Allocator> AsMut<Vec<T, A>> for Vec<T, A> {
+    fn as_mut(&mut self) -> &mut Vec<T, A> {
+        self
+    }
+}
+
+impl<T, A: Allocator> AsRef<[T]> for Vec<T, A> {
+    fn as_ref(&self) -> &[T] {
         self
     }
 }
 
+impl<T, A: Allocator> AsMut<[T]> for Vec<T, A> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+/// Only available with the `panic_on_oom` feature: this panics if allocation fails,
+/// which defeats the purpose of this crate. Use it only to interop with generic code
+/// written against `Extend`, and prefer [`Vec::extend`] otherwise.
+#[cfg(feature = "panic_on_oom")]
+impl<T, A: Allocator> Extend<T> for Vec<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        Vec::extend(self, iter).expect("allocation failed")
+    }
+}
+
+/// Only available with the `panic_on_oom` feature: this collects into the global allocator
+/// and **panics if allocation fails**, which defeats the purpose of this crate.
+/// Use it only to interop with code that relies on `collect()`, and prefer
+/// [`Vec::new_in`] followed by [`Vec::extend`] otherwise.
+#[cfg(feature = "panic_on_oom")]
+impl<T> FromIterator<T> for Vec<T, alloc::alloc::Global> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Vec::new_in(alloc::alloc::Global);
+        Vec::extend(&mut vec, iter).expect("allocation failed");
+        vec
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'data, T: Sync + 'data, A: Allocator> rayon::iter::IntoParallelIterator for &'data Vec<T, A> {
+    type Iter = rayon::slice::Iter<'data, T>;
+    type Item = &'data T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.inner.as_slice().into_par_iter()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'data, T: Send + 'data, A: Allocator> rayon::iter::IntoParallelIterator
+    for &'data mut Vec<T, A>
+{
+    type Iter = rayon::slice::IterMut<'data, T>;
+    type Item = &'data mut T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.inner.as_mut_slice().into_par_iter()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::claim::Claim;
     use crate::testing::{AllowGlobalAllocGuard, NoGlobalAllocGuard};
-    use crate::testing::{WatermarkAllocator, WatermarkAllocator2};
+    use crate::testing::{CountingAllocator, WatermarkAllocator, WatermarkAllocator2};
     use alloc::boxed::Box;
     use alloc::collections::TryReserveError;
+    use alloc::sync::Arc;
     use alloc::{format, vec};
+    use core::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn test_basics() {
@@ -327,6 +1326,145 @@ mod tests {
         assert_eq!(vec.capacity(), 4);
     }
 
+    #[test]
+    fn test_insert_remove() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(16);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1u32, 3]).unwrap();
+        vec.insert(1, 2).unwrap();
+        vec.insert(3, 4).unwrap();
+        let _err: TryReserveError = vec.insert(0, 0).unwrap_err();
+        assert_eq!(vec.as_slice(), &[1, 2, 3, 4]);
+        assert_eq!(vec.capacity(), 4);
+
+        assert_eq!(vec.remove(1), 2);
+        assert_eq!(vec.remove(2), 4);
+        assert_eq!(vec.as_slice(), &[1, 3]);
+        vec.insert(0, 0).unwrap();
+        assert_eq!(vec.as_slice(), &[0, 1, 3]);
+    }
+
+    #[test]
+    fn test_swap_remove() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1u32, 2, 3, 4]).unwrap();
+        let in_use = wma.in_use();
+
+        assert_eq!(vec.swap_remove(1), 2);
+        assert_eq!(vec.as_slice(), &[1, 4, 3]);
+        assert_eq!(vec.swap_remove(2), 3);
+        assert_eq!(vec.as_slice(), &[1, 4]);
+        assert_eq!(wma.in_use(), in_use);
+    }
+
+    #[test]
+    fn test_spare_capacity_mut() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec: Vec<u8, _> = Vec::with_capacity_in(16, wma.clone()).unwrap();
+        vec.push(0).unwrap();
+
+        let spare = vec.spare_capacity_mut();
+        assert_eq!(spare.len(), 15);
+        for (i, slot) in spare[..4].iter_mut().enumerate() {
+            slot.write(i as u8 + 1);
+        }
+        // SAFETY: the four elements after the first were just initialized.
+        unsafe { vec.set_len(5) };
+        assert_eq!(vec.as_slice(), &[0, 1, 2, 3, 4]);
+        assert_eq!(vec.capacity(), 16);
+        assert_eq!(wma.in_use(), 16);
+    }
+
+    #[test]
+    fn test_push_unchecked() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let mut vec = Vec::new_in(ca.clone());
+        vec.reserve(4).unwrap();
+        assert_eq!(ca.allocations(), 1);
+        for value in 1..=4 {
+            assert!(vec.len() < vec.capacity());
+            unsafe { vec.push_unchecked(value) };
+        }
+        assert_eq!(vec.as_slice(), &[1, 2, 3, 4]);
+        assert_eq!(ca.allocations(), 1);
+        assert_eq!(ca.grows(), 0);
+    }
+
+    #[derive(Default)]
+    struct WithDefaultVec {
+        bytes: Vec<u8, alloc::alloc::Global>,
+    }
+
+    #[test]
+    fn test_default() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let mut with_default = WithDefaultVec::default();
+        assert!(with_default.bytes.is_empty());
+        assert_eq!(with_default.bytes.capacity(), 0);
+
+        {
+            let _allow_global_alloc_guard = AllowGlobalAllocGuard::new();
+            with_default.bytes.push(1).unwrap();
+            assert_eq!(with_default.bytes.as_slice(), &[1]);
+            drop(with_default);
+        }
+    }
+
+    #[test]
+    fn test_try_from_exact_iter_in() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let vec = Vec::try_from_exact_iter_in(0..1000, ca.clone()).unwrap();
+        assert_eq!(vec.len(), 1000);
+        assert_eq!(vec.capacity(), 1000);
+        assert!(vec.iter().copied().eq(0..1000));
+        assert_eq!(ca.allocations(), 1);
+        assert_eq!(ca.grows(), 0);
+
+        let wma = WatermarkAllocator::new(16);
+        let _err: TryReserveError = Vec::try_from_exact_iter_in(0..5i32, wma.clone()).unwrap_err();
+        assert_eq!(wma.in_use(), 0);
+    }
+
+    #[test]
+    fn test_try_from_iter_in() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let vec = Vec::try_from_iter_in((0..1000).map(|x| x * 2), ca.clone()).unwrap();
+        assert!(vec.iter().copied().eq((0..1000).map(|x| x * 2)));
+        assert_eq!(ca.allocations(), 1);
+        assert_eq!(ca.grows(), 0);
+
+        // No size hint: grows dynamically.
+        let vec = Vec::try_from_iter_in((0..100).filter(|x| x % 2 == 0), ca.clone()).unwrap();
+        assert!(vec.iter().copied().eq((0..100).step_by(2)));
+    }
+
+    #[test]
+    fn test_try_from_iter_in_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let drops = core::cell::Cell::new(0);
+
+        // Fails in the dynamic growth path after a few elements were built.
+        let iter = (0..32).filter(|_| true).map(|value| DropCounter {
+            drops: &drops,
+            value,
+        });
+        assert!(Vec::try_from_iter_in(iter, wma.clone()).is_err());
+        assert_eq!(wma.in_use(), 0);
+        assert!(drops.get() > 0);
+
+        // Fails reserving the lower bound up front.
+        let _err: TryReserveError = Vec::try_from_iter_in(0..32u64, wma.clone()).unwrap_err();
+        assert_eq!(wma.in_use(), 0);
+    }
+
     #[test]
     fn test_with_capacity_in() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -340,6 +1478,127 @@ mod tests {
         let _err: TryReserveError = Vec::<i8, _>::with_capacity_in(5, wma).unwrap_err();
     }
 
+    #[test]
+    fn test_with_capacity_in_zero() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let vec: Vec<u64, _> = Vec::with_capacity_in(0, ca.clone()).unwrap();
+        assert_eq!(vec.capacity(), 0);
+        assert_eq!(ca.allocations(), 0);
+
+        let wma = WatermarkAllocator::new(0);
+        let vec: Vec<u64, _> = Vec::with_capacity_in(0, wma.clone()).unwrap();
+        assert_eq!(vec.capacity(), 0);
+        assert_eq!(wma.in_use(), 0);
+    }
+
+    #[test]
+    fn test_shrink_respects_capacity_floor() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(256);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.set_capacity_floor(8);
+        assert_eq!(vec.capacity_floor(), 8);
+        assert_eq!(vec.capacity(), 0); // Setting the floor doesn't allocate.
+
+        vec.extend(0..20u64).unwrap();
+        vec.truncate(2);
+        vec.shrink_to_fit().unwrap();
+        assert_eq!(vec.capacity(), 8);
+        assert_eq!(wma.in_use(), 64);
+        assert_eq!(vec.as_slice(), &[0, 1]);
+
+        let cloned = vec.try_clone().unwrap();
+        assert_eq!(cloned.capacity_floor(), 8);
+        drop(cloned);
+
+        vec.shrink_to(4).unwrap();
+        assert_eq!(vec.capacity(), 8);
+
+        vec.extend(2..12).unwrap();
+        vec.shrink_to(10).unwrap();
+        assert_eq!(vec.capacity(), 12);
+
+        vec.set_capacity_floor(0);
+        vec.truncate(3);
+        vec.shrink_to(5).unwrap();
+        assert_eq!(vec.capacity(), 5);
+        vec.shrink_to_fit().unwrap();
+        assert_eq!(vec.capacity(), 3);
+        assert_eq!(wma.in_use(), 3 * size_of::<u64>());
+
+        vec.clear();
+        vec.shrink_to_fit().unwrap();
+        assert_eq!(vec.capacity(), 0);
+        assert_eq!(wma.in_use(), 0);
+        vec.push(42).unwrap();
+        assert_eq!(vec.as_slice(), &[42]);
+    }
+
+    #[test]
+    fn test_shrink_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        // The watermark allocator shrinks by allocating a new buffer, which can't
+        // fit next to the current one.
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::with_capacity_in(8, wma.clone()).unwrap();
+        vec.extend(0..8u64).unwrap();
+        vec.truncate(2);
+        let ptr = vec.as_ptr();
+
+        let _err: TryReserveError = vec.shrink_to_fit().unwrap_err();
+        let _err: TryReserveError = vec.shrink_to(4).unwrap_err();
+        assert_eq!(vec.capacity(), 8);
+        assert_eq!(vec.as_ptr(), ptr);
+        assert_eq!(vec.as_slice(), &[0, 1]);
+        assert_eq!(wma.in_use(), 64);
+
+        // Shrinking to nothing just frees the buffer.
+        vec.clear();
+        vec.shrink_to_fit().unwrap();
+        assert_eq!(vec.capacity(), 0);
+        assert_eq!(wma.in_use(), 0);
+    }
+
+    #[test]
+    fn test_shrink_to_fit_when_full() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let mut vec = Vec::with_capacity_in(4, ca.clone()).unwrap();
+        vec.extend([1u32, 2, 3, 4]).unwrap();
+        let ptr = vec.as_ptr();
+
+        vec.shrink_to_fit().unwrap();
+        vec.shrink_to(2).unwrap();
+        assert_eq!(vec.capacity(), 4);
+        assert_eq!(vec.as_ptr(), ptr);
+        assert_eq!(ca.allocations(), 1);
+        assert_eq!(ca.grows(), 0);
+        assert_eq!(ca.shrinks(), 0);
+    }
+
+    #[test]
+    fn test_reserve_exact() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        // Growing briefly holds both the old and the new buffer.
+        let wma = WatermarkAllocator::new(40 * size_of::<u32>());
+        let mut vec: Vec<u32, _> = Vec::new_in(wma.clone());
+        vec.extend(0..16).unwrap();
+        assert_eq!(vec.capacity(), 16);
+
+        // Amortized growth would double to 32 elements and exceed the budget.
+        let _err: TryReserveError = vec.reserve(4).unwrap_err();
+        vec.reserve_exact(4).unwrap();
+        assert_eq!(vec.capacity(), 20);
+        assert_eq!(wma.in_use(), 20 * size_of::<u32>());
+
+        // Already enough room: a no-op.
+        vec.reserve_exact(4).unwrap();
+        assert_eq!(vec.capacity(), 20);
+
+        let _err: TryReserveError = vec.reserve_exact(usize::MAX).unwrap_err();
+    }
+
     #[test]
     fn test_reserve() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -351,6 +1610,46 @@ mod tests {
         let _err: TryReserveError = vec.reserve(33).unwrap_err();
     }
 
+    #[test]
+    fn test_reserve_amortized_capped() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(40);
+        let mut vec: Vec<i32, _> = Vec::with_capacity_in(4, wma.clone()).unwrap();
+        vec.extend([1, 2, 3, 4]).unwrap();
+
+        // Doubling to 8 elements needs 16 + 32 bytes while growing.
+        let _err: TryReserveError = vec.reserve(1).unwrap_err();
+        assert_eq!(vec.capacity(), 4);
+
+        // Growing to just 5 elements needs 16 + 20 bytes.
+        vec.reserve_amortized_capped(1, 0).unwrap();
+        assert_eq!(vec.capacity(), 5);
+        vec.push(5).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 2, 3, 4, 5]);
+
+        // Already enough capacity: no-op.
+        vec.truncate(3);
+        vec.reserve_amortized_capped(2, 100).unwrap();
+        assert_eq!(vec.capacity(), 5);
+    }
+
+    #[test]
+    fn test_reserve_amortized_capped_growth() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(1024);
+        let mut vec: Vec<u8, _> = Vec::with_capacity_in(10, wma).unwrap();
+        vec.extend_with(10, 0).unwrap();
+
+        // The cap is generous, so this grows like `reserve`.
+        vec.reserve_amortized_capped(1, 100).unwrap();
+        assert_eq!(vec.capacity(), 20);
+
+        // The cap limits the doubling.
+        vec.extend_with(10, 0).unwrap();
+        vec.reserve_amortized_capped(1, 3).unwrap();
+        assert_eq!(vec.capacity(), 24);
+    }
+
     #[test]
     fn test_fmt_debug() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -419,6 +1718,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_as_ptr_range() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma);
+        vec.extend([1u32, 2, 3]).unwrap();
+
+        let range = vec.as_ptr_range();
+        assert_eq!(range.start, vec.as_ptr());
+        assert_eq!(range.end, unsafe { vec.as_ptr().add(vec.len()) });
+
+        let start = vec.as_mut_ptr();
+        let range = vec.as_mut_ptr_range();
+        assert_eq!(range.start, start);
+        assert_eq!(range.end, unsafe { start.add(3) });
+        unsafe { *range.end.sub(1) = 4 };
+        assert_eq!(vec.as_slice(), &[1, 2, 4]);
+    }
+
     #[test]
     fn test_as_mut_ptr() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -458,134 +1776,1119 @@ mod tests {
     }
 
     /// A type that implements `Clone` and `Claim`, but not `Copy`.
-    #[derive(Clone, Eq, PartialEq)]
+    #[derive(Debug, Clone, Eq, PartialEq)]
     struct Claimable(i32);
 
-    impl Claim for Claimable {}
+    impl Claim for Claimable {}
+
+    #[test]
+    fn test_ensure_len() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.push(1u32).unwrap();
+
+        vec.ensure_len(4, 7).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 7, 7, 7]);
+        let in_use = wma.in_use();
+        let capacity = vec.capacity();
+
+        vec.ensure_len(4, 9).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 7, 7, 7]);
+        assert_eq!(wma.in_use(), in_use);
+        assert_eq!(vec.capacity(), capacity);
+
+        vec.ensure_len(2, 9).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 7]);
+    }
+
+    #[test]
+    fn test_try_clone_diagnostic() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::with_capacity_in(3, wma.clone()).unwrap();
+        vec.extend_with(3, 1u64).unwrap();
+        let cloned = vec.try_clone_diagnostic().unwrap();
+        assert_eq!(cloned.as_slice(), vec.as_slice());
+        drop(cloned);
+
+        let mut vec2 = Vec::with_capacity_in(5, wma.clone()).unwrap();
+        vec2.extend_with(5, 2u64).unwrap();
+        let failure = vec2.try_clone_diagnostic().unwrap_err();
+        let layout = failure.layout().unwrap();
+        assert_eq!(layout.size(), vec2.len() * size_of::<u64>());
+        assert_eq!(layout.align(), align_of::<u64>());
+        assert_eq!(failure.requested_bytes(), Some(40));
+    }
+
+    #[test]
+    fn test_try_merge_sorted() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let mut left = Vec::new_in(ca.clone());
+        left.extend_from_slice(&[1, 3, 5]).unwrap();
+        let mut right = Vec::new_in(ca.clone());
+        right.extend_from_slice(&[2, 4, 6]).unwrap();
+        let allocations = ca.allocations();
+
+        let merged = left.try_merge_sorted(right, ca.clone()).unwrap();
+        assert_eq!(merged.as_slice(), &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(ca.allocations(), allocations + 1);
+        assert_eq!(ca.grows(), 0);
+
+        // Equal keys keep the left elements before the right ones.
+        let mut left = Vec::new_in(ca.clone());
+        left.extend([Keyed(1, 'l'), Keyed(1, 'L'), Keyed(2, 'l')])
+            .unwrap();
+        let mut right = Vec::new_in(ca.clone());
+        right
+            .extend([Keyed(1, 'r'), Keyed(2, 'r'), Keyed(3, 'r')])
+            .unwrap();
+        let merged = left.try_merge_sorted(right, ca.clone()).unwrap();
+        let sides: [char; 6] = core::array::from_fn(|i| merged[i].1);
+        assert_eq!(sides, ['l', 'L', 'r', 'l', 'r', 'r']);
+    }
+
+    /// Ordered by the key alone, so elements with the same key compare equal
+    /// whatever their tag.
+    struct Keyed(u32, char);
+
+    impl PartialEq for Keyed {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Keyed {}
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Keyed {
+        fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    #[test]
+    fn test_try_merge_sorted_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut left = Vec::new_in(wma.clone());
+        left.extend_from_slice(&[1u32, 3, 5]).unwrap();
+        let mut right = Vec::new_in(wma.clone());
+        right.extend_from_slice(&[2u32, 4, 6]).unwrap();
+
+        let _err: TryReserveError = left
+            .try_merge_sorted(right, WatermarkAllocator::new(16))
+            .unwrap_err();
+        assert_eq!(wma.in_use(), 0);
+    }
+
+    #[test]
+    fn test_try_grow_zeroed() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(4 << 20);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.try_grow_zeroed(1 << 20).unwrap();
+        assert_eq!(vec.len(), 1 << 20);
+        assert_eq!(vec.capacity(), 1 << 20);
+        assert!(vec.iter().all(|b| *b == 0u8));
+
+        // Leave non-zero bytes in the spare capacity, then grow past it.
+        vec.iter_mut().for_each(|b| *b = 0xff);
+        vec.truncate(16);
+        vec.try_grow_zeroed((2 << 20) - 16).unwrap();
+        assert_eq!(vec.capacity(), 2 << 20);
+        assert!(vec[..16].iter().all(|b| *b == 0xff));
+        assert!(vec[16..].iter().all(|b| *b == 0));
+        assert_eq!(wma.in_use(), 2 << 20);
+
+        // Growing within capacity clears the spare bytes too.
+        vec.iter_mut().for_each(|b| *b = 0xff);
+        vec.truncate(0);
+        vec.try_grow_zeroed(1024).unwrap();
+        assert_eq!(vec.capacity(), 2 << 20);
+        assert!(vec.iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn test_try_grow_zeroed_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1u64, 2]).unwrap();
+        let _err: TryReserveError = vec.try_grow_zeroed(8).unwrap_err();
+        assert_eq!(vec.as_slice(), &[1, 2]);
+        assert_eq!(vec.capacity(), 4);
+
+        let _err: TryReserveError = vec.try_grow_zeroed(usize::MAX).unwrap_err();
+        vec.try_grow_zeroed(2).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 2, 0, 0]);
+    }
+
+    #[test]
+    fn test_as_bytes() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma);
+        vec.extend_from_slice(&[1u32, 0x01020304]).unwrap();
+        assert_eq!(vec.as_bytes().len(), vec.len() * 4);
+        assert_eq!(&vec.as_bytes()[..4], &1u32.to_ne_bytes());
+        assert_eq!(&vec.as_bytes()[4..], &0x01020304u32.to_ne_bytes());
+
+        vec.as_bytes_mut()[..4].copy_from_slice(&7u32.to_ne_bytes());
+        assert_eq!(vec.as_slice(), &[7, 0x01020304]);
+    }
+
+    #[test]
+    fn test_try_dedup_total_last() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let vec = Vec::try_from_slice_in(&[1u32, 2, 1, 3], wma.clone()).unwrap();
+
+        let ca = CountingAllocator::new();
+        let deduped = vec.try_dedup_total_last(ca.clone()).unwrap();
+        assert_eq!(deduped.as_slice(), &[2, 1, 3]);
+        assert_eq!(deduped.capacity(), 3);
+        // The scratch vec of indices and the result.
+        assert_eq!(ca.allocations(), 2);
+        assert_eq!(ca.grows(), 0);
+        assert_eq!(vec.as_slice(), &[1, 2, 1, 3]);
+
+        let vec = Vec::try_from_slice_in(&[3u32, 1, 3, 2, 1], wma.clone()).unwrap();
+        let deduped = vec.try_dedup_total_last(ca.clone()).unwrap();
+        assert_eq!(deduped.as_slice(), &[3, 2, 1]);
+
+        let empty = Vec::<u32, _>::new_in(wma);
+        assert!(empty.try_dedup_total_last(ca).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_try_dedup_total_last_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(16);
+        let vec = Vec::try_from_slice_in(&[1u32, 2, 3, 4], wma.clone()).unwrap();
+        // The scratch vec of indices doesn't fit.
+        let _err: TryReserveError = vec.try_dedup_total_last(wma.clone()).unwrap_err();
+        assert_eq!(wma.in_use(), 16);
+
+        // The scratch vec fits, but the result doesn't fit next to it.
+        let wma2 = WatermarkAllocator::new(4 * size_of::<usize>() + 8);
+        let _err: TryReserveError = vec.try_dedup_total_last(wma2.clone()).unwrap_err();
+        assert_eq!(wma2.in_use(), 0);
+    }
+
+    #[test]
+    fn test_try_from_slice_in() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let vec = Vec::try_from_slice_in(&[1u16, 2, 3], ca.clone()).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 2, 3]);
+        assert_eq!(vec.capacity(), 3);
+        assert_eq!(ca.allocations(), 1);
+        assert_eq!(ca.grows(), 0);
+
+        let claimables = [Claimable(1), Claimable(2)];
+        let vec = Vec::try_from_slice_in(&claimables, ca.clone()).unwrap();
+        assert_eq!(vec.as_slice(), &claimables);
+        assert_eq!(ca.allocations(), 2);
+
+        let wma = WatermarkAllocator::new(4);
+        let _err: TryReserveError = Vec::try_from_slice_in(&[1u16, 2, 3], wma).unwrap_err();
+    }
+
+    #[test]
+    fn test_as_slice_of_cells() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma);
+        vec.extend([1u32, 2, 3, 4]).unwrap();
+
+        let cells = vec.as_slice_of_cells();
+        for window in cells.windows(2) {
+            window[1].set(window[0].get() + window[1].get());
+        }
+        assert_eq!(vec.as_slice(), &[1, 3, 6, 10]);
+    }
+
+    #[test]
+    fn test_swap_with_slice() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend_from_slice(&[1, 2, 3]).unwrap();
+        let in_use = wma.in_use();
+        let mut array = [4, 5, 6];
+        vec.swap_with_slice(&mut array);
+        assert_eq!(vec.as_slice(), &[4, 5, 6]);
+        assert_eq!(array, [1, 2, 3]);
+        assert_eq!(wma.in_use(), in_use);
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Id(u32);
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Point {
+        x: i16,
+        y: i16,
+    }
+
+    crate::claim_copy!(Id, Point);
+
+    #[test]
+    fn test_hash_ignores_allocator() {
+        fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+            let mut hasher = std::hash::DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let wma = WatermarkAllocator::new(64);
+        let mut checked = Vec::new_in(wma);
+        checked.extend([1u32, 2, 3]).unwrap();
+        let mut global = Vec::new_in(alloc::alloc::Global);
+        global.extend([1u32, 2, 3]).unwrap();
+
+        assert_eq!(checked, global);
+        assert_eq!(hash_of(&checked), hash_of(&global));
+        assert_eq!(hash_of(&checked), hash_of(&[1u32, 2, 3][..]));
+
+        global.push(4).unwrap();
+        assert_ne!(hash_of(&checked), hash_of(&global));
+    }
+
+    #[test]
+    fn test_claim_bound() {
+        use core::ops::Bound;
+
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut vec = Vec::new_in(wma);
+        vec.extend([Bound::Included(1u64), Bound::Excluded(5), Bound::Unbounded])
+            .unwrap();
+        let cloned = vec.try_clone().unwrap();
+        assert_eq!(cloned, vec);
+    }
+
+    #[test]
+    fn test_claim_copy() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut ids = Vec::new_in(wma.clone());
+        ids.extend_from_slice(&[Id(1), Id(2)]).unwrap();
+        ids.extend_with(1, Id(3)).unwrap();
+        assert_eq!(ids.as_slice(), &[Id(1), Id(2), Id(3)]);
+
+        let mut points = Vec::new_in(wma);
+        points.extend_with(2, Point { x: 1, y: -1 }).unwrap();
+        let cloned = points.try_clone().unwrap();
+        assert_eq!(cloned.as_slice(), &[Point { x: 1, y: -1 }; 2]);
+    }
+
+    #[test]
+    fn test_extend_from_slice_clone() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(32);
+        let mut vec = Vec::new_in(wma);
+        vec.extend_from_slice(&[Claimable(1), Claimable(2), Claimable(3), Claimable(4)])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_extend_from_slice_copy() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(32);
+        let mut vec = Vec::new_in(wma);
+        vec.extend_from_slice(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(vec.inner.as_slice(), &[1, 2, 3, 4]);
+
+        let _err: TryReserveError = vec.extend_from_slice(&[5, 6]).unwrap_err();
+
+        vec.extend_from_slice(&[]).unwrap();
+    }
+
+    #[test]
+    fn test_extend_from_slice_copy_single_allocation() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let mut vec = Vec::new_in(ca.clone());
+        let slice = [7u64; 4096];
+        vec.extend_from_slice(&slice).unwrap();
+        assert_eq!(vec.len(), slice.len());
+        assert_eq!(vec.as_slice(), &slice[..]);
+        assert_eq!(ca.allocations(), 1);
+        assert_eq!(ca.grows(), 0);
+    }
+
+    #[test]
+    fn test_extend_from_slice_map() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let mut vec: Vec<u32, _> = Vec::new_in(ca.clone());
+        let bytes: [u8; 256] = core::array::from_fn(|i| i as u8);
+        vec.extend_from_slice_map(&bytes, |&b| u32::from(b) << 8)
+            .unwrap();
+        assert_eq!(vec.len(), bytes.len());
+        assert_eq!(vec[255], 255 << 8);
+        assert_eq!(ca.allocations(), 1);
+        assert_eq!(ca.grows(), 0);
+    }
+
+    #[test]
+    fn test_extend_from_slice_map_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(16);
+        let mut vec: Vec<u32, _> = Vec::new_in(wma.clone());
+        vec.extend_from_slice_map(&[1u8, 2], |&b| b.into()).unwrap();
+
+        let mut calls = 0;
+        let _err: TryReserveError = vec
+            .extend_from_slice_map(&[3u8, 4, 5, 6], |&b| {
+                calls += 1;
+                b.into()
+            })
+            .unwrap_err();
+        assert_eq!(calls, 0);
+        assert_eq!(vec.as_slice(), &[1, 2]);
+    }
+
+    #[test]
+    fn test_extend_from_slice_copy_matches_clone() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(1024);
+        let mut copied = Vec::new_in(wma.clone());
+        let mut cloned = Vec::new_in(wma);
+        let chunks: [&[i32]; 4] = [&[1, 2, 3], &[], &[4, 5], &[6, 7, 8, 9, 10]];
+        for chunk in chunks {
+            let claimables: [Claimable; 5] =
+                core::array::from_fn(|i| Claimable(chunk.get(i).copied().unwrap_or(0)));
+            copied.extend_from_slice(chunk).unwrap();
+            cloned
+                .extend_from_slice(&claimables[..chunk.len()])
+                .unwrap();
+        }
+        assert_eq!(copied.as_slice(), &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(copied.len(), cloned.len());
+        assert!(copied.iter().zip(cloned.iter()).all(|(a, b)| *a == b.0));
+    }
+
+    #[test]
+    fn test_deref() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(32);
+        let mut vec = Vec::new_in(wma);
+        vec.push(1).unwrap();
+        vec.push(2).unwrap();
+        vec.push(3).unwrap();
+        vec.push(4).unwrap();
+        assert_eq!(&*vec, &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_deref_mut() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(32);
+        let mut vec = Vec::new_in(wma);
+        vec.push(1).unwrap();
+        vec.push(2).unwrap();
+        vec.push(3).unwrap();
+        vec.push(4).unwrap();
+        let vec: &mut [i32] = &mut vec;
+        vec[0] = 5;
+        vec[1] = 6;
+        vec[2] = 7;
+        vec[3] = 8;
+        assert_eq!(&*vec, &[5, 6, 7, 8]);
+    }
+
+    struct MyIter {
+        counter: usize,
+        min_size_hint: usize,
+    }
+
+    impl MyIter {
+        fn new(min_size_hint: usize) -> Self {
+            Self {
+                counter: 0,
+                min_size_hint,
+            }
+        }
+    }
+
+    impl Iterator for MyIter {
+        type Item = usize;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.counter >= 10 {
+                return None;
+            }
+            self.counter += 1;
+            Some(self.counter - 1)
+        }
+
+        // This sort-of lies, but it's here for testing purposes.
+        // It states that the iterator has at least, just, 5 elements.
+        // This is done so we can get good code coverage and test both
+        // the optimised pre-reserve code path for `extend` and the
+        // slower dynamic re-allocation code path.
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.min_size_hint, None)
+        }
+    }
+
+    #[test]
+    fn test_extend() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+
+        // Test the optimised with mixed pre-reserved and dynamic allocation extend paths.
+        let wma = WatermarkAllocator::new(32 * size_of::<usize>());
+        {
+            let mut vec = Vec::new_in(wma.clone());
+            vec.extend(MyIter::new(5)).unwrap();
+            assert_eq!(vec.inner.as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        }
+        assert_eq!(wma.in_use(), 0);
+
+        // Test with a fully pre-reserved path.
+        {
+            let mut vec = Vec::new_in(wma.clone());
+            vec.extend(MyIter::new(10)).unwrap();
+            assert_eq!(vec.inner.as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        }
+        assert_eq!(wma.in_use(), 0);
+
+        // Test with a fully pre-reserved path, but the `min` size_hint lies
+        // and exceeds the truth.
+        {
+            let mut vec = Vec::new_in(wma.clone());
+            vec.extend(MyIter::new(20)).unwrap();
+            assert_eq!(vec.inner.as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        }
+        assert_eq!(wma.in_use(), 0);
+
+        // The min size hint is zero, all dynamically allocated.
+        {
+            let mut vec = Vec::new_in(wma.clone());
+            vec.extend(MyIter::new(0)).unwrap();
+            assert_eq!(vec.inner.as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        }
+        assert_eq!(wma.in_use(), 0);
+    }
+
+    #[test]
+    fn test_extend_rolls_back_on_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let drops = core::cell::Cell::new(0);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend((0..2).map(|value| DropCounter {
+            drops: &drops,
+            value,
+        }))
+        .unwrap();
+
+        // No size hint, so the error happens in the dynamic path after a few pushes.
+        let mut pulled = 0;
+        let iter = (2..64).filter(|_| true).map(|value| {
+            pulled += 1;
+            DropCounter {
+                drops: &drops,
+                value,
+            }
+        });
+        assert!(vec.extend(iter).is_err());
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec[1].value, 1);
+        assert!(pulled > 1);
+        assert_eq!(drops.get(), pulled);
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum ParseError {
+        Invalid(i32),
+        Alloc(TryReserveError),
+    }
+
+    impl From<TryReserveError> for ParseError {
+        fn from(e: TryReserveError) -> Self {
+            ParseError::Alloc(e)
+        }
+    }
+
+    fn parse(value: i32) -> Result<i32, ParseError> {
+        if value < 0 {
+            Err(ParseError::Invalid(value))
+        } else {
+            Ok(value * 10)
+        }
+    }
+
+    #[test]
+    fn test_try_extend_from_results() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma);
+        vec.push(0).unwrap();
+        vec.try_extend_from_results([1, 2, 3].into_iter().map(parse))
+            .unwrap();
+        assert_eq!(vec.as_slice(), &[0, 10, 20, 30]);
+    }
+
+    #[test]
+    fn test_try_extend_from_results_user_error() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma);
+        let err = vec
+            .try_extend_from_results([1, 2, -3, 4].into_iter().map(parse))
+            .unwrap_err();
+        assert_eq!(err, ParseError::Invalid(-3));
+        assert_eq!(vec.as_slice(), &[10, 20]);
+    }
+
+    #[test]
+    fn test_try_extend_from_results_alloc_error() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(16);
+        let mut vec = Vec::new_in(wma);
+        let err = vec
+            .try_extend_from_results(MyIter::new(0).map(|v| parse(v as i32)))
+            .unwrap_err();
+        assert!(matches!(err, ParseError::Alloc(_)));
+        assert_eq!(vec.as_slice(), &[0, 10, 20, 30]);
+    }
+
+    #[test]
+    fn test_splice() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1, 2, 3, 4, 5]).unwrap();
+        let removed = vec.splice(1..3, [10, 11, 12]).unwrap();
+        assert_eq!(removed.as_slice(), &[2, 3]);
+        assert_eq!(vec.as_slice(), &[1, 10, 11, 12, 4, 5]);
+
+        let removed = vec.splice(4.., []).unwrap();
+        assert_eq!(removed.as_slice(), &[4, 5]);
+        assert_eq!(vec.as_slice(), &[1, 10, 11, 12]);
+    }
+
+    #[test]
+    fn test_splice_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(32);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1, 2, 3, 4]).unwrap();
+        assert_eq!(wma.in_use(), 16);
+        let _err: TryReserveError = vec.splice(1..3, [10, 11, 12]).unwrap_err();
+        assert_eq!(vec.as_slice(), &[1, 2, 3, 4]);
+        assert_eq!(wma.in_use(), 16);
+    }
+
+    #[test]
+    fn test_group_runs_by_key() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma);
+        vec.extend([1, 1, 2, 3, 3, 3]).unwrap();
+        let mut runs = vec.group_runs_by_key(|x| *x);
+        assert_eq!(runs.next(), Some(&[1, 1][..]));
+        assert_eq!(runs.next(), Some(&[2][..]));
+        assert_eq!(runs.next(), Some(&[3, 3, 3][..]));
+        assert_eq!(runs.next(), None);
+
+        let mut lens = [0; 3];
+        for (len, run) in lens.iter_mut().zip(vec.group_runs_by_key(|x| *x)) {
+            *len = run.len();
+        }
+        assert_eq!(lens, [2, 1, 3]);
+
+        assert_eq!(vec.group_runs_by_key(|x| *x % 2).count(), 3);
+        vec.clear();
+        assert_eq!(vec.group_runs_by_key(|x| *x).next(), None);
+    }
+
+    #[test]
+    fn test_sort_unstable_dedup() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([3, 1, 2, 3, 1]).unwrap();
+        let in_use = wma.in_use();
+        vec.sort_unstable_dedup();
+        assert_eq!(vec.as_slice(), &[1, 2, 3]);
+        assert_eq!(wma.in_use(), in_use);
+    }
+
+    #[test]
+    fn test_append_range_from() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut src = Vec::new_in(wma.clone());
+        src.extend([1, 2, 3, 4, 5]).unwrap();
+        let mut dst = Vec::new_in(wma.clone());
+        dst.push(0).unwrap();
+        dst.append_range_from(&mut src, 1..4).unwrap();
+        assert_eq!(dst.as_slice(), &[0, 2, 3, 4]);
+        assert_eq!(src.as_slice(), &[1, 5]);
+        dst.append_range_from(&mut src, ..).unwrap();
+        assert_eq!(dst.as_slice(), &[0, 2, 3, 4, 1, 5]);
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn test_append_range_from_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(32);
+        let mut src = Vec::new_in(wma.clone());
+        src.extend([1, 2, 3, 4, 5]).unwrap();
+        let mut dst = Vec::new_in(wma.clone());
+        let _err: TryReserveError = dst.append_range_from(&mut src, 1..4).unwrap_err();
+        assert_eq!(src.as_slice(), &[1, 2, 3, 4, 5]);
+        assert!(dst.is_empty());
+    }
+
+    fn join_digits<A: Allocator>(
+        mut acc: Vec<u8, A>,
+        digit: &u8,
+    ) -> Result<Vec<u8, A>, TryReserveError> {
+        if !acc.is_empty() {
+            acc.push(b',')?;
+        }
+        acc.push(b'0' + digit)?;
+        Ok(acc)
+    }
+
+    #[test]
+    fn test_append_deque() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut deque = VecDeque::with_capacity_in(4, wma.clone()).unwrap();
+        deque.extend([0u32, 1, 2, 3]).unwrap();
+        deque.pop_front();
+        deque.pop_front();
+        deque.extend([4, 5]).unwrap();
+        assert!(!deque.as_slices().1.is_empty()); // The buffer wraps around.
+
+        let mut vec = Vec::new_in(wma.clone());
+        vec.push(1).unwrap();
+        vec.append_deque(&mut deque).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 2, 3, 4, 5]);
+        assert!(deque.is_empty());
+        assert_eq!(deque.capacity(), 4);
+    }
+
+    #[test]
+    fn test_append_deque_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(32);
+        let mut deque = VecDeque::new_in(wma.clone());
+        deque.extend([1u32, 2, 3, 4]).unwrap();
+        let mut vec = Vec::with_capacity_in(1, wma.clone()).unwrap();
+        vec.push(0).unwrap();
+
+        let _err: TryReserveError = vec.append_deque(&mut deque).unwrap_err();
+        assert_eq!(vec.as_slice(), &[0]);
+        assert_eq!(deque.make_contiguous(), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_try_fold() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut digits = Vec::new_in(wma.clone());
+        digits.extend([1u8, 2, 3, 4]).unwrap();
+        let joined = digits
+            .try_fold(Vec::new_in(wma.clone()), join_digits)
+            .unwrap();
+        assert_eq!(joined.as_slice(), b"1,2,3,4");
+    }
+
+    #[test]
+    fn test_try_fold_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(24);
+        let mut digits = Vec::new_in(wma.clone());
+        digits.extend([1u8, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+        let in_use = wma.in_use();
+        let _err: TryReserveError = digits
+            .try_fold(Vec::new_in(wma.clone()), join_digits)
+            .unwrap_err();
+        assert_eq!(wma.in_use(), in_use);
+    }
+
+    #[test]
+    fn test_max_min_by_key() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut vec = Vec::new_in(wma);
+        assert_eq!(vec.max_by_key(|s: &&str| s.len()), None);
+
+        vec.extend(["pear", "fig", "banana", "kiwi", "cherry"])
+            .unwrap();
+        assert_eq!(vec.max_by_key(|s| s.len()), Some(&"cherry"));
+        assert_eq!(vec.min_by_key(|s| s.len()), Some(&"fig"));
+        assert_eq!(vec.min_by_key(|s| s.len() / 3), Some(&"pear"));
+    }
+
+    #[test]
+    fn test_fill_indexed() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend_with(5, 7usize).unwrap();
+        let in_use = wma.in_use();
+
+        vec.fill_indexed(|i| i * i);
+        assert_eq!(vec.as_slice(), &[0, 1, 4, 9, 16]);
+        assert_eq!(wma.in_use(), in_use);
+    }
+
+    #[test]
+    fn test_try_for_each() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1u32, 2, 3, 4, 5, 6]).unwrap();
+
+        let mut copy = Vec::new_in(wma.clone());
+        vec.try_for_each(|x| copy.push(*x)).unwrap();
+        assert_eq!(copy.as_slice(), vec.as_slice());
+
+        // Only room for 4 elements in the copy.
+        let mut copy = Vec::new_in(WatermarkAllocator::new(16));
+        let mut visited = 0;
+        let _err: TryReserveError = vec
+            .try_for_each(|x| {
+                visited += 1;
+                copy.push(*x)
+            })
+            .unwrap_err();
+        assert_eq!(copy.as_slice(), &[1, 2, 3, 4]);
+        assert_eq!(visited, 5);
+    }
+
+    #[test]
+    fn test_split() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma);
+        vec.extend([1u8, 0, 2, 0, 3]).unwrap();
+
+        let mut split = vec.split(|x| *x == 0);
+        assert_eq!(split.next(), Some(&[1][..]));
+        assert_eq!(split.next(), Some(&[2][..]));
+        assert_eq!(split.next(), Some(&[3][..]));
+        assert_eq!(split.next(), None);
+
+        let mut rsplit = vec.rsplit(|x| *x == 0);
+        assert_eq!(rsplit.next(), Some(&[3][..]));
+        assert_eq!(rsplit.next(), Some(&[2][..]));
+        assert_eq!(rsplit.next(), Some(&[1][..]));
+        assert_eq!(rsplit.next(), None);
+    }
+
+    #[cfg(feature = "num-traits")]
+    #[test]
+    fn test_sum_copied_and_mean() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec: Vec<f64, _> = Vec::new_in(wma.clone());
+        assert_eq!(vec.sum_copied(), 0.0);
+        assert!(vec.mean().is_nan());
+
+        vec.extend([1.5, 2.5, 3.0, 5.0]).unwrap();
+        assert_eq!(vec.sum_copied(), 12.0);
+        assert_eq!(vec.mean(), 3.0);
+
+        let mut ints: Vec<u32, _> = Vec::new_in(wma.clone());
+        assert_eq!(ints.sum_copied(), 0);
+        ints.extend([1, 2, 4]).unwrap();
+        assert_eq!(ints.sum_copied(), 7);
+        assert_eq!(ints.mean(), 7.0 / 3.0);
+        drop(ints);
+
+        let mut timestamps: Vec<i64, _> = Vec::new_in(wma.clone());
+        timestamps.extend([-1_000, 2_000, 5_000]).unwrap();
+        assert_eq!(timestamps.sum_copied(), 6_000);
+        assert_eq!(timestamps.mean(), 2_000.0);
+        drop(timestamps);
+
+        let mut sizes: Vec<usize, _> = Vec::new_in(wma);
+        sizes.extend([1, 2]).unwrap();
+        assert_eq!(sizes.mean(), 1.5);
+    }
+
+    #[test]
+    fn test_count_runs() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma);
+        assert_eq!(vec.count_runs(), 0);
+        vec.push(7).unwrap();
+        assert_eq!(vec.count_runs(), 1);
+        vec.clear();
+        vec.extend([1, 1, 2, 3, 3, 3]).unwrap();
+        assert_eq!(vec.count_runs(), 3);
+        vec.push(1).unwrap();
+        assert_eq!(vec.count_runs(), 4);
+    }
+
+    #[test]
+    fn test_chunk_by() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma);
+        vec.extend([1, 1, 2, 2, 2, 3]).unwrap();
+
+        let mut runs = vec.chunk_by(|a, b| a == b);
+        assert_eq!(runs.next(), Some(&[1, 1][..]));
+        assert_eq!(runs.next(), Some(&[2, 2, 2][..]));
+        assert_eq!(runs.next(), Some(&[3][..]));
+        assert_eq!(runs.next(), None);
+
+        let mut ascending = vec.chunk_by(|a, b| a < b);
+        assert_eq!(ascending.next(), Some(&[1][..]));
+        assert_eq!(ascending.next(), Some(&[1, 2][..]));
+    }
+
+    #[test]
+    fn test_clear_each() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1u32, 2, 3, 4]).unwrap();
+        let capacity = vec.capacity();
+        let in_use = wma.in_use();
+
+        let mut count = 0;
+        let mut sum = 0;
+        vec.clear_each(|x| {
+            count += 1;
+            sum = sum * 10 + x;
+        });
+        assert_eq!(count, 4);
+        assert_eq!(sum, 1234); // Visited front to back.
+        assert!(vec.is_empty());
+        assert_eq!(vec.capacity(), capacity);
+        assert_eq!(wma.in_use(), in_use);
+    }
+
+    #[test]
+    fn test_insert_iter() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(256);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1usize, 2, 3]).unwrap();
+        vec.insert_iter(1, 10..13).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 10, 11, 12, 2, 3]);
+
+        // The iterator's size hint under-reports its length.
+        vec.insert_iter(6, MyIter::new(2)).unwrap();
+        assert_eq!(vec.len(), 16);
+        assert_eq!(&vec[5..], &[3, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        vec.insert_iter(0, core::iter::empty()).unwrap();
+        assert_eq!(vec.len(), 16);
+    }
+
+    #[test]
+    fn test_insert_iter_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(7 * size_of::<usize>());
+        let mut vec = Vec::with_capacity_in(4, wma.clone()).unwrap();
+        vec.extend([1usize, 2, 3]).unwrap();
+
+        // Only one of the elements fits without growing, and growing fails.
+        let _err: TryReserveError = vec.insert_iter(1, MyIter::new(0)).unwrap_err();
+        assert_eq!(vec.as_slice(), &[1, 2, 3]);
+        assert_eq!(vec.capacity(), 4);
+    }
+
+    #[test]
+    fn test_insert_sorted_unique() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(16);
+        let mut vec = Vec::new_in(wma.clone());
+        assert_eq!(vec.insert_sorted_unique(5u32).unwrap(), Err(0));
+        assert_eq!(vec.insert_sorted_unique(1).unwrap(), Err(0));
+        assert_eq!(vec.insert_sorted_unique(3).unwrap(), Err(1));
+        assert_eq!(vec.insert_sorted_unique(7).unwrap(), Err(3));
+        assert_eq!(vec.as_slice(), &[1, 3, 5, 7]);
+
+        // The vec is full: finding an existing element must not allocate.
+        assert_eq!(wma.in_use(), 16);
+        assert_eq!(vec.insert_sorted_unique(3).unwrap(), Ok(1));
+        assert_eq!(vec.insert_sorted_unique(7).unwrap(), Ok(3));
+        let _err: TryReserveError = vec.insert_sorted_unique(4).unwrap_err();
+        assert_eq!(vec.as_slice(), &[1, 3, 5, 7]);
+    }
+
+    #[test]
+    fn test_into_inner_and_from_inner() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1u32, 2, 3]).unwrap();
+        let ptr = vec.as_ptr();
+        let in_use = wma.in_use();
+
+        let mut inner: InnerVec<u32, _> = vec.into_inner();
+        assert_eq!(inner.as_ptr(), ptr);
+        inner.retain(|x| *x != 2);
 
-    #[test]
-    fn test_extend_from_slice_clone() {
-        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
-        let wma = WatermarkAllocator::new(32);
-        let mut vec = Vec::new_in(wma);
-        vec.extend_from_slice(&[Claimable(1), Claimable(2), Claimable(3), Claimable(4)])
-            .unwrap();
+        let vec = Vec::from(inner);
+        assert_eq!(vec.as_slice(), &[1, 3]);
+        assert_eq!(vec.as_ptr(), ptr);
+        assert_eq!(vec.capacity_floor(), 0);
+        assert_eq!(wma.in_use(), in_use);
     }
 
     #[test]
-    fn test_extend_from_slice_copy() {
+    fn test_into_iter() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
-        let wma = WatermarkAllocator::new(32);
-        let mut vec = Vec::new_in(wma);
-        vec.extend_from_slice(&[1, 2, 3, 4]).unwrap();
-        assert_eq!(vec.inner.as_slice(), &[1, 2, 3, 4]);
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1u32, 2, 3, 4]).unwrap();
 
-        let _err: TryReserveError = vec.extend_from_slice(&[5, 6]).unwrap_err();
+        for x in &mut vec {
+            *x *= 10;
+        }
+        let mut sum = 0;
+        for x in &vec {
+            sum += x;
+        }
+        assert_eq!(sum, 100);
 
-        vec.extend_from_slice(&[]).unwrap();
+        let mut iter = vec.into_iter();
+        assert_eq!(iter.next(), Some(10));
+        assert_eq!(iter.next_back(), Some(40));
+        assert_eq!(wma.in_use(), 16);
+        drop(iter);
+        assert_eq!(wma.in_use(), 0);
     }
 
     #[test]
-    fn test_deref() {
+    fn test_truncate_each() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
-        let wma = WatermarkAllocator::new(32);
+        let wma = WatermarkAllocator::new(64);
         let mut vec = Vec::new_in(wma);
-        vec.push(1).unwrap();
-        vec.push(2).unwrap();
-        vec.push(3).unwrap();
-        vec.push(4).unwrap();
-        assert_eq!(&*vec, &[1, 2, 3, 4]);
+        vec.extend([1u32, 2, 3, 4, 5]).unwrap();
+        let capacity = vec.capacity();
+
+        let mut sum = 0;
+        let mut order = 0;
+        vec.truncate_each(2, |x| {
+            sum += x;
+            order = order * 10 + x;
+        });
+        assert_eq!(sum, 12);
+        assert_eq!(order, 345);
+        assert_eq!(vec.as_slice(), &[1, 2]);
+        assert_eq!(vec.capacity(), capacity);
+
+        vec.truncate_each(5, |_| unreachable!());
+        assert_eq!(vec.as_slice(), &[1, 2]);
     }
 
     #[test]
-    fn test_deref_mut() {
+    fn test_drain() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
-        let wma = WatermarkAllocator::new(32);
-        let mut vec = Vec::new_in(wma);
-        vec.push(1).unwrap();
-        vec.push(2).unwrap();
-        vec.push(3).unwrap();
-        vec.push(4).unwrap();
-        let vec: &mut [i32] = &mut vec;
-        vec[0] = 5;
-        vec[1] = 6;
-        vec[2] = 7;
-        vec[3] = 8;
-        assert_eq!(&*vec, &[5, 6, 7, 8]);
-    }
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1u32, 2, 3, 4, 5]).unwrap();
+        let capacity = vec.capacity();
+        let in_use = wma.in_use();
 
-    struct MyIter {
-        counter: usize,
-        min_size_hint: usize,
+        assert!(vec.drain(1..3).eq([2, 3]));
+        assert_eq!(vec.as_slice(), &[1, 4, 5]);
+        assert!(vec.drain(..).eq([1, 4, 5]));
+        assert!(vec.is_empty());
+        assert_eq!(vec.capacity(), capacity);
+        assert_eq!(wma.in_use(), in_use);
+
+        // Leaking the iterator leaves the vec truncated but usable.
+        vec.extend([1, 2, 3, 4]).unwrap();
+        core::mem::forget(vec.drain(1..3));
+        assert_eq!(vec.as_slice(), &[1]);
+        vec.push(6).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 6]);
     }
 
-    impl MyIter {
-        fn new(min_size_hint: usize) -> Self {
-            Self {
-                counter: 0,
-                min_size_hint,
-            }
-        }
+    #[test]
+    fn test_retain() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let drops = core::cell::Cell::new(0);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend((1..=6).map(|value| DropCounter {
+            drops: &drops,
+            value,
+        }))
+        .unwrap();
+        let in_use = wma.in_use();
+
+        vec.retain(|x| x.value % 2 == 0);
+        assert_eq!(drops.get(), 3);
+        assert!(vec.iter().map(|x| x.value).eq([2, 4, 6]));
+
+        vec.retain_mut(|x| {
+            x.value *= 10;
+            x.value != 40
+        });
+        assert_eq!(drops.get(), 4);
+        assert!(vec.iter().map(|x| x.value).eq([20, 60]));
+        assert_eq!(wma.in_use(), in_use);
     }
 
-    impl Iterator for MyIter {
-        type Item = usize;
+    #[test]
+    fn test_retain_unordered() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1u32, 2, 3, 4, 5, 6, 7, 8, 10, 12]).unwrap();
+        let mut expected = vec.try_clone().unwrap();
+        let in_use = wma.in_use();
 
-        fn next(&mut self) -> Option<Self::Item> {
-            if self.counter >= 10 {
-                return None;
-            }
-            self.counter += 1;
-            Some(self.counter - 1)
-        }
+        vec.retain_unordered(|x| x % 2 == 1 || *x > 7);
+        expected.inner.retain(|x| x % 2 == 1 || *x > 7);
+        vec.sort_unstable();
+        assert_eq!(vec.as_slice(), expected.as_slice());
+        assert_eq!(wma.in_use(), in_use);
 
-        // This sort-of lies, but it's here for testing purposes.
-        // It states that the iterator has at least, just, 5 elements.
-        // This is done so we can get good code coverage and test both
-        // the optimised pre-reserve code path for `extend` and the
-        // slower dynamic re-allocation code path.
-        fn size_hint(&self) -> (usize, Option<usize>) {
-            (self.min_size_hint, None)
-        }
+        vec.retain_unordered(|_| false);
+        assert!(vec.is_empty());
     }
 
     #[test]
-    fn test_extend() {
+    fn test_take_matching() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1, 2, 3, 4, 5, 6, 7]).unwrap();
+        let evens = vec.take_matching(|x| x % 2 == 0).unwrap();
+        assert_eq!(evens.as_slice(), &[2, 4, 6]);
+        assert_eq!(vec.as_slice(), &[1, 3, 5, 7]);
+        assert_eq!(evens.capacity(), 3);
 
-        // Test the optimised with mixed pre-reserved and dynamic allocation extend paths.
-        let wma = WatermarkAllocator::new(32 * size_of::<usize>());
-        {
-            let mut vec = Vec::new_in(wma.clone());
-            vec.extend(MyIter::new(5)).unwrap();
-            assert_eq!(vec.inner.as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
-        }
-        assert_eq!(wma.in_use(), 0);
-
-        // Test with a fully pre-reserved path.
-        {
-            let mut vec = Vec::new_in(wma.clone());
-            vec.extend(MyIter::new(10)).unwrap();
-            assert_eq!(vec.inner.as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
-        }
-        assert_eq!(wma.in_use(), 0);
-
-        // Test with a fully pre-reserved path, but the `min` size_hint lies
-        // and exceeds the truth.
-        {
-            let mut vec = Vec::new_in(wma.clone());
-            vec.extend(MyIter::new(20)).unwrap();
-            assert_eq!(vec.inner.as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
-        }
-        assert_eq!(wma.in_use(), 0);
+        let none = vec.take_matching(|x| *x > 100).unwrap();
+        assert!(none.is_empty());
+        assert_eq!(vec.as_slice(), &[1, 3, 5, 7]);
+    }
 
-        // The min size hint is zero, all dynamically allocated.
-        {
-            let mut vec = Vec::new_in(wma.clone());
-            vec.extend(MyIter::new(0)).unwrap();
-            assert_eq!(vec.inner.as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
-        }
-        assert_eq!(wma.in_use(), 0);
+    #[test]
+    fn test_take_matching_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(32);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1, 2, 3, 4, 5, 6, 7]).unwrap();
+        let _err: TryReserveError = vec.take_matching(|x| x % 2 == 0).unwrap_err();
+        assert_eq!(vec.as_slice(), &[1, 2, 3, 4, 5, 6, 7]);
     }
 
     #[test]
@@ -604,6 +2907,77 @@ mod tests {
         assert_eq!(vec.inner.as_slice(), empty);
     }
 
+    #[test]
+    fn test_clear_where() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([0, 1, 0, 0, 2, 3, 0]).unwrap();
+        let capacity = vec.capacity();
+        assert_eq!(vec.clear_where(|x| *x == 0), 4);
+        assert_eq!(vec.as_slice(), &[1, 2, 3]);
+        assert_eq!(vec.capacity(), capacity);
+        assert_eq!(vec.clear_where(|x| *x == 0), 0);
+    }
+
+    #[test]
+    fn test_drain_tail() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1, 2, 3, 4, 5]).unwrap();
+        let mut undo = Vec::new_in(wma.clone());
+        undo.extend(vec.drain_tail(2)).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 2]);
+        assert_eq!(undo.as_slice(), &[3, 4, 5]);
+        assert_eq!(vec.drain_tail(2).count(), 0);
+        assert_eq!(vec.capacity(), 5);
+    }
+
+    #[derive(Clone)]
+    struct DropCounter<'a> {
+        drops: &'a core::cell::Cell<usize>,
+        value: i32,
+    }
+
+    impl Claim for DropCounter<'_> {}
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_keep_last() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let drops = core::cell::Cell::new(0);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend((1..=5).map(|value| DropCounter {
+            drops: &drops,
+            value,
+        }))
+        .unwrap();
+        let in_use = wma.in_use();
+
+        vec.keep_last(2);
+        assert_eq!(drops.get(), 3);
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec[0].value, 4);
+        assert_eq!(vec[1].value, 5);
+        assert_eq!(wma.in_use(), in_use);
+
+        vec.keep_last(2);
+        vec.keep_last(10);
+        assert_eq!(drops.get(), 3);
+        assert_eq!(vec.len(), 2);
+
+        vec.keep_last(0);
+        assert_eq!(drops.get(), 5);
+        assert!(vec.is_empty());
+    }
+
     #[test]
     fn test_extend_with() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -613,6 +2987,29 @@ mod tests {
         assert_eq!(vec.inner.as_slice(), &[1, 1, 1]);
     }
 
+    #[test]
+    fn test_capacity_overflow() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let is_overflow =
+            |e: TryReserveError| matches!(e.kind(), TryReserveErrorKind::CapacityOverflow);
+
+        let mut vec = Vec::new_in(wma.clone());
+        vec.push(1u8).unwrap();
+        assert!(is_overflow(vec.extend_with(usize::MAX, 2).unwrap_err()));
+        assert!(is_overflow(vec.resize(usize::MAX, 2).unwrap_err()));
+        assert!(is_overflow(vec.reserve(usize::MAX).unwrap_err()));
+        assert_eq!(vec.as_slice(), &[1]);
+
+        // Zero-sized types never allocate, so the length is the only thing that can overflow.
+        let mut zsts = Vec::new_in(wma.clone());
+        zsts.push(()).unwrap();
+        assert!(is_overflow(zsts.extend_with(usize::MAX, ()).unwrap_err()));
+        assert!(is_overflow(zsts.reserve(usize::MAX).unwrap_err()));
+        assert_eq!(zsts.len(), 1);
+        assert_eq!(wma.in_use(), vec.capacity());
+    }
+
     #[test]
     fn test_resize() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -626,6 +3023,34 @@ mod tests {
         assert_eq!(vec.inner.as_slice(), &[1, 1]);
     }
 
+    #[test]
+    fn test_shift_left() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1, 2, 3, 4]).unwrap();
+        let in_use = wma.in_use();
+        vec.shift_left(2, 0);
+        assert_eq!(vec.as_slice(), &[3, 4, 0, 0]);
+        vec.shift_left(0, 9);
+        assert_eq!(vec.as_slice(), &[3, 4, 0, 0]);
+        vec.shift_left(10, 7);
+        assert_eq!(vec.as_slice(), &[7, 7, 7, 7]);
+        assert_eq!(wma.in_use(), in_use);
+    }
+
+    #[test]
+    fn test_shift_right() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma.clone());
+        vec.extend([1, 2, 3, 4]).unwrap();
+        vec.shift_right(1, 0);
+        assert_eq!(vec.as_slice(), &[0, 1, 2, 3]);
+        vec.shift_right(4, 5);
+        assert_eq!(vec.as_slice(), &[5, 5, 5, 5]);
+    }
+
     #[test]
     fn test_resize_with() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -832,6 +3257,265 @@ mod tests {
         assert_eq!(d0vec2, 2);
     }
 
+    #[test]
+    fn test_try_clone_single_allocation() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let mut vec = Vec::with_capacity_in(64 * 1024, ca.clone()).unwrap();
+        vec.resize(64 * 1024, 0xABu8).unwrap();
+        assert_eq!(ca.allocations(), 1);
+
+        let cloned = vec.try_clone().unwrap();
+        assert_eq!(ca.allocations(), 2);
+        assert_eq!(ca.grows(), 0);
+        assert_eq!(cloned.capacity(), 64 * 1024);
+        assert_eq!(cloned, vec);
+    }
+
+    #[test]
+    fn test_try_into_flattened() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let mut nested = Vec::new_in(ca.clone());
+        for slice in [&[1, 2][..], &[3], &[], &[4, 5]] {
+            let mut vec = Vec::new_in(ca.clone());
+            vec.extend_from_slice(slice).unwrap();
+            nested.push(vec).unwrap();
+        }
+        let allocations = ca.allocations();
+
+        let flattened = nested.try_into_flattened(ca.clone()).unwrap();
+        assert_eq!(flattened.as_slice(), &[1, 2, 3, 4, 5]);
+        assert_eq!(flattened.capacity(), 5);
+        assert_eq!(ca.allocations(), allocations + 1);
+        assert_eq!(ca.grows(), 0);
+    }
+
+    #[test]
+    fn test_try_into_flattened_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(1024);
+        let mut nested = Vec::new_in(wma.clone());
+        for _ in 0..3 {
+            let mut vec = Vec::new_in(wma.clone());
+            vec.extend_with(4, 1u64).unwrap();
+            nested.push(vec).unwrap();
+        }
+        let _err: TryReserveError = nested
+            .try_into_flattened(WatermarkAllocator::new(64))
+            .unwrap_err();
+        assert_eq!(wma.in_use(), 0);
+    }
+
+    struct BigThing<'a> {
+        data: [u8; 1024],
+        clones: &'a AtomicUsize,
+    }
+
+    impl Clone for BigThing<'_> {
+        fn clone(&self) -> Self {
+            self.clones.fetch_add(1, Ordering::Relaxed);
+            Self {
+                data: self.data,
+                clones: self.clones,
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_clone_arc_shares_pointees() {
+        let clones = AtomicUsize::new(0);
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut vec = Vec::new_in(wma.clone());
+        {
+            let _allow_global_alloc_guard = AllowGlobalAllocGuard::new();
+            for i in 0..3 {
+                let thing = BigThing {
+                    data: [i; 1024],
+                    clones: &clones,
+                };
+                vec.push(Arc::new(thing)).unwrap();
+            }
+        }
+
+        // Cloning the vec only bumps the reference counts: no `BigThing` is cloned.
+        let cloned = vec.try_clone().unwrap();
+        assert_eq!(clones.load(Ordering::Relaxed), 0);
+        assert_eq!(
+            wma.in_use(),
+            (vec.capacity() + cloned.capacity()) * size_of::<Arc<BigThing>>()
+        );
+        for (original, clone) in vec.iter().zip(cloned.iter()) {
+            assert!(Arc::ptr_eq(original, clone));
+            assert_eq!(Arc::strong_count(original), 2);
+        }
+        assert_eq!(cloned[2].data[0], 2);
+
+        let _allow_global_alloc_guard = AllowGlobalAllocGuard::new();
+        drop(cloned);
+        assert!(vec.iter().all(|thing| Arc::strong_count(thing) == 1));
+        drop(vec);
+    }
+
+    #[test]
+    fn test_try_clone_nested() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(1024);
+        let mut outer = Vec::new_in(wma.clone());
+        for len in 1..=3u8 {
+            let mut inner = Vec::new_in(wma.clone());
+            inner.extend(0..len).unwrap();
+            outer.push(inner).unwrap();
+        }
+        let in_use = wma.in_use();
+
+        let cloned = outer.try_clone().unwrap();
+        assert_eq!(cloned.len(), 3);
+        assert_eq!(cloned[0].as_slice(), &[0]);
+        assert_eq!(cloned[1].as_slice(), &[0, 1]);
+        assert_eq!(cloned[2].as_slice(), &[0, 1, 2]);
+        assert_eq!(
+            wma.in_use() - in_use,
+            3 * size_of::<Vec<u8, WatermarkAllocator>>() + 1 + 2 + 3
+        );
+
+        // Doubly nested vecs clone too.
+        let mut nested = Vec::new_in(wma.clone());
+        nested.push(cloned).unwrap();
+        let nested_clone = nested.try_clone().unwrap();
+        assert_eq!(nested_clone[0][2].as_slice(), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn test_try_clone_nested_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        // Room for both outer vecs, the 4 original inner vecs and just 2 inner clones.
+        let outer_size = 4 * size_of::<Vec<u8, WatermarkAllocator>>();
+        let wma = WatermarkAllocator::new(2 * outer_size + 6 * 16 + 8);
+        let mut outer = Vec::with_capacity_in(4, wma.clone()).unwrap();
+        for _ in 0..4 {
+            let mut inner = Vec::new_in(wma.clone());
+            inner.extend_with(16, 7u8).unwrap();
+            outer.push(inner).unwrap();
+        }
+        let in_use = wma.in_use();
+        assert_eq!(in_use, outer_size + 4 * 16);
+        let _err: TryReserveError = outer.try_clone().unwrap_err();
+        assert_eq!(wma.in_use(), in_use);
+    }
+
+    #[test]
+    fn test_try_clone_nested_failure_drops_partial_clone() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        // Room for both outer vecs, the 4 original inner vecs and just 2 inner clones.
+        let outer_size = 4 * size_of::<Vec<DropCounter, WatermarkAllocator>>();
+        let element_size = size_of::<DropCounter>();
+        let wma = WatermarkAllocator::new(2 * outer_size + 6 * element_size + 8);
+        let drops = core::cell::Cell::new(0);
+        let mut outer = Vec::with_capacity_in(4, wma.clone()).unwrap();
+        for value in 0..4 {
+            let mut inner = Vec::with_capacity_in(1, wma.clone()).unwrap();
+            inner
+                .push(DropCounter {
+                    drops: &drops,
+                    value,
+                })
+                .unwrap();
+            outer.push(inner).unwrap();
+        }
+        let in_use = wma.in_use();
+
+        // Cloning the third inner vec fails: exactly the two elements cloned so far are dropped.
+        assert!(outer.try_clone().is_err());
+        assert_eq!(drops.get(), 2);
+        assert_eq!(wma.in_use(), in_use);
+        let values = outer.iter().map(|inner| inner[0].value);
+        assert!(values.eq(0..4));
+
+        drop(outer);
+        assert_eq!(drops.get(), 6);
+        assert_eq!(wma.in_use(), 0);
+    }
+
+    #[cfg(feature = "either")]
+    #[test]
+    fn test_try_clone_either() {
+        use either::Either;
+
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(256);
+        let mut vec: Vec<Either<u32, u64>, _> = Vec::new_in(wma);
+        vec.push(Either::Left(1)).unwrap();
+        vec.push(Either::Right(2)).unwrap();
+        let cloned = vec.try_clone().unwrap();
+        assert_eq!(cloned, vec);
+        assert_eq!(cloned[0], Either::Left(1));
+        assert_eq!(cloned[1], Either::Right(2));
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_try_clone_uuid() {
+        use uuid::Uuid;
+
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(256);
+        let mut vec: Vec<Uuid, _> = Vec::new_in(wma);
+        vec.push(Uuid::nil()).unwrap();
+        vec.push(Uuid::max()).unwrap();
+        let cloned = vec.try_clone().unwrap();
+        assert_eq!(cloned, vec);
+        assert_eq!(cloned[1], Uuid::max());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_iter() {
+        use rayon::iter::{IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(1 << 20);
+        let mut vec = Vec::new_in(wma);
+        vec.extend(0..100_000u64).unwrap();
+        let sequential: u64 = vec.iter().sum();
+
+        // The rayon thread pool allocates globally for its own bookkeeping.
+        let _allow_global_alloc_guard = AllowGlobalAllocGuard::new();
+        let parallel: u64 = vec.par_iter().sum();
+        assert_eq!(parallel, sequential);
+
+        vec.par_iter_mut().for_each(|x| *x *= 2);
+        let doubled: u64 = vec.par_iter().sum();
+        assert_eq!(doubled, sequential * 2);
+    }
+
+    #[cfg(feature = "panic_on_oom")]
+    #[test]
+    fn test_extend_trait() {
+        fn extend_generic<E: Extend<u32>>(target: &mut E) {
+            target.extend([1, 2, 3]);
+        }
+
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = Vec::new_in(wma);
+        vec.push(0).unwrap();
+        extend_generic(&mut vec);
+        assert_eq!(vec.as_slice(), &[0, 1, 2, 3]);
+    }
+
+    #[cfg(feature = "panic_on_oom")]
+    #[test]
+    fn test_from_iterator() {
+        let vec: Vec<u32, alloc::alloc::Global> = (1..=5).collect();
+        assert_eq!(vec.as_slice(), &[1, 2, 3, 4, 5]);
+
+        let evens = vec.iter().filter(|x| *x % 2 == 0).copied();
+        let evens: Vec<u32, alloc::alloc::Global> = evens.collect();
+        assert_eq!(evens.as_slice(), &[2, 4]);
+    }
+
     #[test]
     fn test_try_clone() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
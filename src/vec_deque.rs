@@ -3,13 +3,25 @@ use crate::try_clone::TryClone;
 use alloc::collections::vec_deque::{Drain, VecDeque as InnerVecDeque};
 use alloc::collections::vec_deque::{Iter, IterMut};
 use alloc::collections::TryReserveError;
+use alloc::vec::Vec as InnerVec;
 use core::alloc::Allocator;
+use core::mem::ManuallyDrop;
 use core::ops::RangeBounds;
 
 pub struct VecDeque<T, A: Allocator> {
     inner: InnerVecDeque<T, A>,
 }
 
+/// The error returned by [`VecDeque::try_insert`]. Both variants hand back the item.
+#[derive(Debug)]
+pub enum InsertError<T> {
+    /// The index was greater than the deque's length.
+    OutOfBounds(T),
+
+    /// Reserving space for the item failed.
+    AllocError(TryReserveError, T),
+}
+
 impl<T, A: Allocator> VecDeque<T, A> {
     #[inline]
     pub fn new_in(alloc: A) -> Self {
@@ -33,6 +45,30 @@ impl<T, A: Allocator> VecDeque<T, A> {
         self.inner.get_mut(index)
     }
 
+    /// Returns mutable references to the elements at the given logical indices at once,
+    /// or `None` if any index is out of bounds or repeated.
+    pub fn get_disjoint_mut<const N: usize>(&mut self, indices: [usize; N]) -> Option<[&mut T; N]> {
+        let len = self.len();
+        for (i, index) in indices.iter().enumerate() {
+            if *index >= len || indices[..i].contains(index) {
+                return None;
+            }
+        }
+        let (front, back) = self.inner.as_mut_slices();
+        let front_len = front.len();
+        let front = front.as_mut_ptr();
+        let back = back.as_mut_ptr();
+
+        // SAFETY: all indices are in bounds and distinct, so the references don't alias.
+        Some(indices.map(|index| unsafe {
+            if index < front_len {
+                &mut *front.add(index)
+            } else {
+                &mut *back.add(index - front_len)
+            }
+        }))
+    }
+
     #[inline]
     pub fn capacity(&self) -> usize {
         self.inner.capacity()
@@ -71,6 +107,17 @@ impl<T, A: Allocator> VecDeque<T, A> {
         self.inner.range(range)
     }
 
+    /// Splits the deque into iterators over the logical ranges `0..mid` and `mid..len`,
+    /// without rearranging the elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid` is greater than the deque's length.
+    #[inline]
+    pub fn split_at(&self, mid: usize) -> (Iter<'_, T>, Iter<'_, T>) {
+        (self.inner.range(..mid), self.inner.range(mid..))
+    }
+
     #[inline]
     pub fn range_mut<R>(&mut self, range: R) -> IterMut<'_, T>
     where
@@ -80,6 +127,7 @@ impl<T, A: Allocator> VecDeque<T, A> {
     }
 
     #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
     pub fn reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
         self.inner.try_reserve(additional)
     }
@@ -115,6 +163,17 @@ impl<T, A: Allocator> VecDeque<T, A> {
         self.inner.front_mut()
     }
 
+    /// Returns the `n`-th element from the front, where `n = 0` is the front.
+    #[inline]
+    pub fn peek_front_nth(&self, n: usize) -> Option<&T> {
+        self.inner.get(n)
+    }
+
+    #[inline]
+    pub fn peek_front_nth_mut(&mut self, n: usize) -> Option<&mut T> {
+        self.inner.get_mut(n)
+    }
+
     #[inline]
     pub fn back(&self) -> Option<&T> {
         self.inner.back()
@@ -135,7 +194,32 @@ impl<T, A: Allocator> VecDeque<T, A> {
         self.inner.pop_back()
     }
 
+    /// Drops up to `n` elements from the front, stopping early if the deque empties.
+    /// Nothing is allocated.
+    #[inline]
+    pub fn advance_front(&mut self, n: usize) {
+        self.inner.drain(..n.min(self.len()));
+    }
+
+    /// Keeps only the last `n` elements, dropping the excess from the front.
+    /// Does nothing if `n >= len`.
+    #[inline]
+    pub fn keep_last(&mut self, n: usize) {
+        let len = self.len();
+        if n < len {
+            self.inner.drain(..len - n);
+        }
+    }
+
+    /// Keeps only the first `n` elements, dropping the excess from the back.
+    /// Does nothing if `n >= len`.
+    #[inline]
+    pub fn keep_first(&mut self, n: usize) {
+        self.inner.truncate(n);
+    }
+
     #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
     pub fn push_front(&mut self, item: T) -> Result<(), TryReserveError> {
         self.reserve(1)?;
         self.inner.push_front(item);
@@ -143,35 +227,224 @@ impl<T, A: Allocator> VecDeque<T, A> {
     }
 
     #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
     pub fn push_back(&mut self, item: T) -> Result<(), TryReserveError> {
         self.reserve(1)?;
         self.inner.push_back(item);
         Ok(())
     }
 
+    /// Inserts `item` at `index`, shifting the elements after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the deque's length.
+    /// See [`VecDeque::try_insert`] for a non-panicking variant.
     #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
     pub fn insert(&mut self, index: usize, item: T) -> Result<(), TryReserveError> {
         self.reserve(1)?;
         self.inner.insert(index, item);
         Ok(())
     }
 
+    /// Inserts `item` at `index`, handing the item back on failure
+    /// instead of panicking when `index` is out of bounds.
+    #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn try_insert(&mut self, index: usize, item: T) -> Result<(), InsertError<T>> {
+        if index > self.len() {
+            return Err(InsertError::OutOfBounds(item));
+        }
+        if let Err(error) = self.reserve(1) {
+            return Err(InsertError::AllocError(error, item));
+        }
+        self.inner.insert(index, item);
+        Ok(())
+    }
+
+    /// Removes and returns the element at `index`, or `None` if it is out of bounds.
     #[inline]
     pub fn remove(&mut self, index: usize) -> Option<T> {
         self.inner.remove(index)
     }
 
+    /// Same as [`VecDeque::remove`], which never panics.
     #[inline]
+    pub fn try_remove(&mut self, index: usize) -> Option<T> {
+        self.remove(index)
+    }
+
+    /// Moves all the elements of `other` to the back of the deque, leaving `other` empty.
+    /// If reserving space fails, neither deque is modified.
+    #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
     pub fn append(&mut self, other: &mut Self) -> Result<(), TryReserveError> {
         self.reserve(other.len())?;
+        // The capacity now covers `len + other.len()` elements. Any wraparound is handled
+        // by moving elements within the existing buffer, so this won't reallocate.
         self.inner.append(&mut other.inner);
         Ok(())
     }
 
+    /// Pushes each element of `iter` to the back of the deque, reserving space for
+    /// the iterator's lower size bound up front.
+    ///
+    /// This is all-or-nothing: on failure the elements pushed by this call are dropped,
+    /// leaving the deque as it was. Items already pulled from the iterator are not given
+    /// back, so any side effects of `next()` remain.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), TryReserveError> {
+        let len = self.len();
+        let iter = iter.into_iter();
+        let (lower_bound, _) = iter.size_hint();
+        self.reserve(lower_bound)?;
+        for item in iter {
+            if let Err(error) = self.push_back(item) {
+                self.inner.truncate(len);
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes each element of `iter` to the front of the deque, in turn.
+    ///
+    /// Like repeated calls to [`VecDeque::push_front`], the elements end up in the
+    /// reverse order of the iterator: extending `[3]` with `[2, 1]` yields `[1, 2, 3]`.
+    ///
+    /// Like [`VecDeque::extend`], this is all-or-nothing: on failure the elements pushed
+    /// by this call are dropped, leaving the deque as it was.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn extend_front<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), TryReserveError> {
+        let len = self.len();
+        let iter = iter.into_iter();
+        let (lower_bound, _) = iter.size_hint();
+        self.reserve(lower_bound)?;
+        for item in iter {
+            if let Err(error) = self.push_front(item) {
+                self.inner.drain(..self.len() - len);
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn make_contiguous(&mut self) -> &mut [T] {
         self.inner.make_contiguous()
     }
+
+    /// Calls `f` on consecutive chunks of `size` elements, in logical order.
+    /// The last chunk is shorter if `size` doesn't divide the length.
+    ///
+    /// A chunk can't straddle the end of the buffer, so this first makes the deque
+    /// contiguous, which moves the elements in place and never allocates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    #[inline]
+    pub fn for_each_chunk(&mut self, size: usize, f: impl FnMut(&[T])) {
+        self.inner.make_contiguous().chunks(size).for_each(f);
+    }
+
+    /// Moves the elements so that the front of the deque sits at the start of the buffer.
+    /// Afterwards [`VecDeque::as_slices`] returns all the elements in its first slice.
+    ///
+    /// The elements are rotated in place: this never allocates.
+    pub fn align_front(&mut self) {
+        // Converting to a vec makes the elements contiguous from the start of the buffer,
+        // and converting back keeps them there.
+        drop(RestoreOnDrop::new(&mut self.inner));
+    }
+
+    /// Shrinks the capacity as much as possible, but not below the length.
+    #[inline]
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn shrink_to_fit(&mut self) -> Result<(), TryReserveError> {
+        self.shrink_to(0)
+    }
+
+    /// Shrinks the capacity to `min_capacity`, but not below the length.
+    /// Does nothing if the capacity is already lower.
+    ///
+    /// Unlike std, a failure to reallocate is reported rather than aborting,
+    /// in which case the deque still holds all its elements.
+    #[must_use = "ignoring the error hides an allocation failure"]
+    pub fn shrink_to(&mut self, min_capacity: usize) -> Result<(), TryReserveError> {
+        let target = min_capacity.max(self.len());
+        if target >= self.capacity() {
+            return Ok(());
+        }
+        // The guard writes the (possibly shrunk) buffer back into `self.inner` when
+        // dropped, even if the allocator panics.
+        let mut guard = RestoreOnDrop::new(&mut self.inner);
+        guard.vec.shrink_to(target)
+    }
+
+    #[inline]
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        self.inner.as_slices()
+    }
+
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        self.inner.as_mut_slices()
+    }
+}
+
+/// Holds a deque's buffer as a vec while it is being worked on, and moves it back
+/// into the deque when dropped.
+struct RestoreOnDrop<'a, T, A: Allocator> {
+    slot: &'a mut InnerVecDeque<T, A>,
+    vec: ManuallyDrop<crate::vec::Vec<T, A>>,
+}
+
+impl<'a, T, A: Allocator> RestoreOnDrop<'a, T, A> {
+    fn new(slot: &'a mut InnerVecDeque<T, A>) -> Self {
+        // SAFETY: the read value is only moved back into `slot` on drop, so it is never
+        // observed or dropped twice. Converting to a vec reuses the buffer and can't panic.
+        let vec = unsafe { core::ptr::read(slot) };
+        Self {
+            slot,
+            vec: ManuallyDrop::new(InnerVec::from(vec).into()),
+        }
+    }
+}
+
+impl<T, A: Allocator> Drop for RestoreOnDrop<'_, T, A> {
+    fn drop(&mut self) {
+        // SAFETY: `vec` is taken exactly once, and `slot` holds a moved-out value
+        // that must be overwritten without being dropped.
+        unsafe {
+            let vec = ManuallyDrop::take(&mut self.vec);
+            core::ptr::write(self.slot, vec.into_inner().into());
+        }
+    }
+}
+
+impl<T: Claim, A: Allocator> VecDeque<T, A> {
+    /// Creates a deque holding clones of the elements of `slice`, in order,
+    /// allocating exactly `slice.len()` elements once.
+    #[inline]
+    pub fn try_from_slice_in(slice: &[T], alloc: A) -> Result<Self, TryReserveError> {
+        Ok(crate::vec::Vec::try_from_slice_in(slice, alloc)?.into())
+    }
+
+    /// Clones up to `dst.len()` elements, in logical order, into `dst`.
+    /// Returns how many were copied. The deque is not rearranged and nothing is allocated.
+    pub fn copy_to_slice(&self, dst: &mut [T]) -> usize {
+        let copied = self.len().min(dst.len());
+        let (front, back) = self.inner.as_slices();
+        let from_front = front.len().min(copied);
+        dst[..from_front].clone_from_slice(&front[..from_front]);
+        dst[from_front..copied].clone_from_slice(&back[..copied - from_front]);
+        copied
+    }
 }
 
 impl<T: Claim, A: Allocator + Claim> TryClone for VecDeque<T, A> {
@@ -182,6 +455,52 @@ impl<T: Claim, A: Allocator + Claim> TryClone for VecDeque<T, A> {
         cloned.inner.extend(self.iter().cloned());
         Ok(cloned)
     }
+
+    /// Reuses the existing buffer (and allocator), only growing it if `source` doesn't fit.
+    /// On failure `self` is left unchanged.
+    fn try_clone_from(&mut self, source: &Self) -> Result<(), Self::Error> {
+        self.reserve(source.len().saturating_sub(self.len()))?;
+        self.inner.clear();
+        // We reserved space for all the elements up front, so this won't allocate.
+        self.inner.extend(source.iter().cloned());
+        Ok(())
+    }
+}
+
+/// Checked vecs can't be `Claim`, so they are cloned element by element.
+/// If cloning an element fails, the elements cloned so far are dropped.
+impl<T, A1, A2> TryClone for VecDeque<crate::vec::Vec<T, A1>, A2>
+where
+    A1: Allocator,
+    A2: Allocator + Claim,
+    crate::vec::Vec<T, A1>: TryClone<Error = TryReserveError>,
+{
+    type Error = TryReserveError;
+
+    fn try_clone(&self) -> Result<Self, Self::Error> {
+        let mut cloned = Self::with_capacity_in(self.len(), self.allocator().clone())?;
+        for vec in self.iter() {
+            // We reserved space for all the elements up front, so this won't allocate.
+            cloned.inner.push_back(vec.try_clone()?);
+        }
+        Ok(cloned)
+    }
+}
+
+impl<T, A: Allocator + Default> Default for VecDeque<T, A> {
+    fn default() -> Self {
+        Self::new_in(A::default())
+    }
+}
+
+/// Only available with the `panic_on_oom` feature: this panics if allocation fails,
+/// which defeats the purpose of this crate. Use it only to interop with generic code
+/// written against `Extend`, and prefer [`VecDeque::extend`] otherwise.
+#[cfg(feature = "panic_on_oom")]
+impl<T, A: Allocator> Extend<T> for VecDeque<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        VecDeque::extend(self, iter).expect("allocation failed")
+    }
 }
 
 impl<T, A: Allocator> From<crate::vec::Vec<T, A>> for VecDeque<T, A> {
@@ -195,8 +514,8 @@ impl<T, A: Allocator> From<crate::vec::Vec<T, A>> for VecDeque<T, A> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::testing::{AllowGlobalAllocGuard, NoGlobalAllocGuard, WatermarkAllocator};
-    use alloc::vec::Vec as InnerVec;
+    use crate::testing::{AllowGlobalAllocGuard, NoGlobalAllocGuard};
+    use crate::testing::{CountingAllocator, WatermarkAllocator};
 
     #[test]
     fn test_new_in() {
@@ -208,6 +527,26 @@ mod tests {
         assert_eq!(wma.in_use(), 0);
     }
 
+    #[derive(Default)]
+    struct WithDefaultDeque {
+        deque: VecDeque<u8, alloc::alloc::Global>,
+    }
+
+    #[test]
+    fn test_default() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let mut with_default = WithDefaultDeque::default();
+        assert!(with_default.deque.is_empty());
+        assert_eq!(with_default.deque.capacity(), 0);
+
+        {
+            let _allow_global_alloc_guard = AllowGlobalAllocGuard::new();
+            with_default.deque.push_back(1).unwrap();
+            assert_eq!(with_default.deque.front(), Some(&1));
+            drop(with_default);
+        }
+    }
+
     #[test]
     fn test_with_capacity_in_success() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -217,6 +556,20 @@ mod tests {
         assert_eq!(wma.in_use(), deque.unwrap().capacity() * size_of::<i32>());
     }
 
+    #[test]
+    fn test_with_capacity_in_zero() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let deque: VecDeque<u64, _> = VecDeque::with_capacity_in(0, ca.clone()).unwrap();
+        assert_eq!(deque.capacity(), 0);
+        assert_eq!(ca.allocations(), 0);
+
+        let wma = WatermarkAllocator::new(0);
+        let deque: VecDeque<u64, _> = VecDeque::with_capacity_in(0, wma.clone()).unwrap();
+        assert_eq!(deque.capacity(), 0);
+        assert_eq!(wma.in_use(), 0);
+    }
+
     #[test]
     fn test_with_capacity_in_failure() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -226,6 +579,92 @@ mod tests {
         assert_eq!(wma.in_use(), 0);
     }
 
+    #[test]
+    fn test_from_vec_reuses_buffer() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut vec = crate::vec::Vec::with_capacity_in(6, wma.clone()).unwrap();
+        vec.extend([1u32, 2, 3]).unwrap();
+        let ptr = vec.as_ptr();
+        let in_use = wma.in_use();
+
+        let mut deque = VecDeque::from(vec);
+        assert_eq!(deque.allocator().in_use(), in_use);
+        assert_eq!(deque.capacity(), 6);
+        assert_eq!(deque.as_slices().0.as_ptr(), ptr);
+        assert_eq!(deque.make_contiguous(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_try_from_slice_in() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(16);
+        let mut deque = VecDeque::try_from_slice_in(&[1u32, 2, 3], wma.clone()).unwrap();
+        assert_eq!(deque.as_slices(), (&[1, 2, 3][..], &[][..]));
+        assert_eq!(deque.capacity(), 3);
+        assert_eq!(wma.in_use(), 3 * size_of::<u32>());
+        assert_eq!(deque.pop_front(), Some(1));
+
+        assert!(VecDeque::try_from_slice_in(&[4u32, 5], wma.clone()).is_err());
+        assert_eq!(wma.in_use(), 3 * size_of::<u32>());
+    }
+
+    #[test]
+    fn test_extend() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(16);
+        let mut deque = VecDeque::new_in(wma.clone());
+        deque.push_back(1u32).unwrap();
+        deque.extend([2, 3]).unwrap();
+        assert_eq!(deque.make_contiguous(), &[1, 2, 3]);
+
+        assert!(deque.extend(4..10).is_err());
+        assert_eq!(deque.make_contiguous(), &[1, 2, 3]);
+
+        // No size hint, so the error happens after some elements were pushed.
+        assert!(deque.extend((4..10).filter(|_| true)).is_err());
+        assert_eq!(deque.make_contiguous(), &[1, 2, 3]);
+    }
+
+    #[cfg(feature = "panic_on_oom")]
+    #[test]
+    fn test_extend_trait() {
+        fn extend_generic<E: Extend<u32>>(target: &mut E) {
+            target.extend([1, 2, 3]);
+        }
+
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut deque = VecDeque::new_in(wma);
+        deque.push_back(0).unwrap();
+        extend_generic(&mut deque);
+        assert_eq!(deque.make_contiguous(), &[0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_copy_to_slice() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut deque = VecDeque::with_capacity_in(4, wma.clone()).unwrap();
+        for i in 0..4 {
+            deque.push_back(i).unwrap();
+        }
+        deque.pop_front();
+        deque.pop_front();
+        deque.push_back(4).unwrap();
+        deque.push_back(5).unwrap();
+        assert!(!deque.as_slices().1.is_empty()); // The buffer wraps around.
+
+        let mut short = [0; 3];
+        assert_eq!(deque.copy_to_slice(&mut short), 3);
+        assert_eq!(short, [2, 3, 4]);
+
+        let mut long = [0; 6];
+        assert_eq!(deque.copy_to_slice(&mut long), 4);
+        assert_eq!(long, [2, 3, 4, 5, 0, 0]);
+        assert!(!deque.as_slices().1.is_empty());
+    }
+
     #[test]
     fn test_push_front_back() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -288,6 +727,65 @@ mod tests {
         assert!(deque.insert(1, 2).is_err());
     }
 
+    #[test]
+    fn test_try_insert() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut deque = VecDeque::new_in(wma.clone());
+        deque.push_back(1).unwrap();
+        deque.push_back(3).unwrap();
+        deque.try_insert(1, 2).unwrap();
+        deque.try_insert(3, 4).unwrap();
+        assert!(matches!(
+            deque.try_insert(5, 6),
+            Err(InsertError::OutOfBounds(6))
+        ));
+        assert_eq!(deque.len(), 4);
+        assert_eq!(deque.try_remove(1), Some(2));
+        assert_eq!(deque.try_remove(3), None);
+        assert_eq!(deque.len(), 3);
+    }
+
+    #[test]
+    fn test_try_insert_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(16);
+        let mut deque = VecDeque::with_capacity_in(1, wma.clone()).expect("should allocate");
+        deque.push_back(1).unwrap();
+        assert!(matches!(
+            deque.try_insert(0, 2),
+            Err(InsertError::AllocError(_, 2))
+        ));
+        assert_eq!(deque.len(), 1);
+        assert_eq!(deque.front(), Some(&1));
+    }
+
+    #[test]
+    fn test_extend_front() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut deque = VecDeque::new_in(wma.clone());
+        deque.push_back(4).unwrap();
+        deque.extend_front([3, 2, 1]).unwrap();
+        assert_eq!(deque.len(), 4);
+        assert_eq!(deque.make_contiguous(), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_extend_front_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(16);
+        let mut deque = VecDeque::new_in(wma.clone());
+        deque.push_back(0).unwrap();
+        assert!(deque.extend_front([1, 2, 3, 4, 5]).is_err());
+        assert_eq!(deque.len(), 1);
+        assert_eq!(deque.front(), Some(&0));
+
+        // No size hint, so the error happens after some elements were pushed.
+        assert!(deque.extend_front((1..6).filter(|_| true)).is_err());
+        assert_eq!(deque.make_contiguous(), &[0]);
+    }
+
     #[test]
     fn test_append() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -329,7 +827,37 @@ mod tests {
 
         // Append should fail due to insufficient allocation capacity in deque1
         assert!(deque1.append(&mut deque2).is_err());
-        assert!(!deque2.is_empty()); // deque2 should remain intact
+        assert_eq!(deque2.as_slices(), (&[1, 2][..], &[][..])); // deque2 should remain intact
+        assert!(deque1.is_empty());
+        assert_eq!(deque1.capacity(), 1);
+        assert_eq!(wma.in_use(), 12);
+    }
+
+    #[test]
+    fn test_append_no_realloc_after_reserve() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let mut deque1 = VecDeque::with_capacity_in(6, ca.clone()).unwrap();
+        for i in 0..6 {
+            deque1.push_back(i).unwrap();
+        }
+        for _ in 0..4 {
+            deque1.pop_front();
+        }
+        deque1.push_back(6).unwrap();
+        let mut deque2 = VecDeque::new_in(ca.clone());
+        deque2.extend_front([9, 8, 7]).unwrap();
+        let allocations = ca.allocations();
+        let grows = ca.grows();
+
+        // deque1 wraps around the end of its buffer, and the append fills it exactly.
+        assert_eq!(deque1.len() + deque2.len(), deque1.capacity());
+        deque1.append(&mut deque2).unwrap();
+        assert_eq!(ca.allocations(), allocations);
+        assert_eq!(ca.grows(), grows);
+        assert_eq!(deque1.capacity(), 6);
+        assert!(deque2.is_empty());
+        assert_eq!(deque1.make_contiguous(), &[4, 5, 6, 7, 8, 9]);
     }
 
     #[test]
@@ -360,6 +888,189 @@ mod tests {
         assert!(cloned.is_err());
     }
 
+    #[test]
+    fn test_try_clone_nested() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(1024);
+        let mut deque = VecDeque::new_in(wma.clone());
+        for len in 1..=3u8 {
+            let mut vec = crate::vec::Vec::new_in(wma.clone());
+            vec.extend(0..len).unwrap();
+            deque.push_front(vec).unwrap();
+        }
+
+        let cloned = deque.try_clone().unwrap();
+        assert_eq!(cloned.len(), 3);
+        assert_eq!(cloned.get(0).unwrap().as_slice(), &[0, 1, 2]);
+        assert_eq!(cloned.get(1).unwrap().as_slice(), &[0, 1]);
+        assert_eq!(cloned.get(2).unwrap().as_slice(), &[0]);
+    }
+
+    #[test]
+    fn test_try_clone_nested_allocation_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        // Room for both deques, the 4 original vecs and just 2 vec clones.
+        let deque_size = 4 * size_of::<crate::vec::Vec<u8, WatermarkAllocator>>();
+        let wma = WatermarkAllocator::new(2 * deque_size + 6 * 16 + 8);
+        let mut deque = VecDeque::with_capacity_in(4, wma.clone()).unwrap();
+        for _ in 0..4 {
+            let mut vec = crate::vec::Vec::new_in(wma.clone());
+            vec.extend_with(16, 7u8).unwrap();
+            deque.push_back(vec).unwrap();
+        }
+        let in_use = wma.in_use();
+        assert_eq!(in_use, deque_size + 4 * 16);
+
+        assert!(deque.try_clone().is_err());
+        assert_eq!(wma.in_use(), in_use);
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let mut deque = VecDeque::with_capacity_in(4, ca.clone()).unwrap();
+        deque.extend([0u32, 1, 2, 3]).unwrap();
+
+        // Already full: nothing to do.
+        deque.shrink_to_fit().unwrap();
+        assert_eq!(deque.capacity(), 4);
+        assert_eq!(ca.allocations(), 1);
+        assert_eq!(ca.shrinks(), 0);
+
+        deque.reserve(12).unwrap();
+        deque.pop_front();
+        deque.push_back(4).unwrap();
+        deque.shrink_to(6).unwrap();
+        assert_eq!(deque.capacity(), 6);
+        deque.shrink_to_fit().unwrap();
+        assert_eq!(deque.capacity(), 4);
+        assert_eq!(ca.shrinks(), 2);
+        assert_eq!(deque.make_contiguous(), &[1, 2, 3, 4]);
+
+        deque.clear();
+        deque.shrink_to_fit().unwrap();
+        assert_eq!(deque.capacity(), 0);
+        deque.push_back(5).unwrap();
+        assert_eq!(deque.front(), Some(&5));
+    }
+
+    #[test]
+    fn test_shrink_to_fit_wrapped() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut deque = VecDeque::with_capacity_in(8, wma.clone()).unwrap();
+        deque.extend(0u32..8).unwrap();
+        for _ in 0..6 {
+            deque.pop_front();
+        }
+        deque.extend([8, 9]).unwrap();
+        assert!(!deque.as_slices().1.is_empty()); // The buffer wraps around.
+
+        deque.shrink_to_fit().unwrap();
+        assert_eq!(deque.capacity(), 4);
+        assert_eq!(wma.in_use(), 4 * size_of::<u32>());
+        assert_eq!(deque.make_contiguous(), &[6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_advance_front() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut deque = VecDeque::new_in(wma.clone());
+        deque.extend([1u32, 2, 3, 4, 5]).unwrap();
+        let capacity = deque.capacity();
+
+        deque.advance_front(3);
+        assert_eq!(deque.make_contiguous(), &[4, 5]);
+        deque.advance_front(0);
+        assert_eq!(deque.len(), 2);
+        deque.advance_front(10);
+        assert!(deque.is_empty());
+        assert_eq!(deque.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_split_at() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(64);
+        let mut deque = VecDeque::with_capacity_in(5, wma).unwrap();
+        deque.extend([0u32, 1, 2, 3, 4]).unwrap();
+        for _ in 0..3 {
+            deque.pop_front();
+        }
+        deque.extend([5, 6, 7]).unwrap();
+        assert!(!deque.as_slices().1.is_empty()); // The buffer wraps around.
+
+        let (front, back) = deque.split_at(2);
+        assert!(front.eq(&[3, 4]));
+        assert!(back.eq(&[5, 6, 7]));
+
+        let (front, back) = deque.split_at(5);
+        assert_eq!(front.len(), 5);
+        assert_eq!(back.len(), 0);
+    }
+
+    #[test]
+    fn test_for_each_chunk() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut deque = VecDeque::with_capacity_in(8, wma.clone()).unwrap();
+        for i in 0..8 {
+            deque.push_back(i).unwrap();
+        }
+        for _ in 0..5 {
+            deque.pop_front();
+        }
+        for i in 8..12 {
+            deque.push_back(i).unwrap();
+        }
+        assert!(!deque.as_slices().1.is_empty()); // The buffer wraps around.
+        assert_eq!(deque.len(), 7);
+        let in_use = wma.in_use();
+
+        let mut chunks = [[0; 3]; 3];
+        let mut lens = [0; 3];
+        let mut count = 0;
+        deque.for_each_chunk(3, |chunk| {
+            chunks[count][..chunk.len()].copy_from_slice(chunk);
+            lens[count] = chunk.len();
+            count += 1;
+        });
+        assert_eq!(count, 3);
+        assert_eq!(lens, [3, 3, 1]);
+        assert_eq!(chunks, [[5, 6, 7], [8, 9, 10], [11, 0, 0]]);
+        assert_eq!(wma.in_use(), in_use);
+    }
+
+    #[test]
+    fn test_align_front() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut deque = VecDeque::with_capacity_in(4, wma.clone()).unwrap();
+        for i in 0..4 {
+            deque.push_back(i).unwrap();
+        }
+        deque.pop_front();
+        deque.pop_front();
+        deque.push_back(4).unwrap();
+        deque.push_back(5).unwrap();
+        assert!(!deque.as_slices().1.is_empty()); // The buffer wraps around.
+        let capacity = deque.capacity();
+        let in_use = wma.in_use();
+
+        deque.align_front();
+        assert_eq!(deque.as_slices(), (&[2, 3, 4, 5][..], &[][..]));
+        assert_eq!(deque.capacity(), capacity);
+        assert_eq!(wma.in_use(), in_use);
+
+        deque.pop_front();
+        deque.align_front();
+        assert_eq!(deque.as_slices(), (&[3, 4, 5][..], &[][..]));
+        deque.push_back(6).unwrap();
+        assert_eq!(deque.as_slices(), (&[3, 4, 5, 6][..], &[][..]));
+    }
+
     #[test]
     fn test_get_mut() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -374,6 +1085,27 @@ mod tests {
         assert_eq!(deque.get(1), Some(&3));
     }
 
+    #[test]
+    fn test_get_disjoint_mut() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut deque = VecDeque::with_capacity_in(4, wma.clone()).unwrap();
+        deque.push_back(3).unwrap();
+        deque.push_back(4).unwrap();
+        deque.push_front(2).unwrap();
+        deque.push_front(1).unwrap();
+        assert!(!deque.inner.as_slices().1.is_empty()); // The buffer wraps around.
+
+        let [first, last] = deque.get_disjoint_mut([0, 3]).unwrap();
+        *first *= 10;
+        *last *= 10;
+        assert_eq!(deque.make_contiguous(), &[10, 2, 3, 40]);
+
+        assert!(deque.get_disjoint_mut([1, 1]).is_none());
+        assert!(deque.get_disjoint_mut([0, 4]).is_none());
+        assert!(deque.get_disjoint_mut([]).is_some());
+    }
+
     #[test]
     fn test_iter() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -533,6 +1265,24 @@ mod tests {
         assert_eq!(deque.front(), Some(&7));
     }
 
+    #[test]
+    fn test_peek_front_nth() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut deque = VecDeque::new_in(wma.clone());
+        deque.push_back(2).unwrap();
+        deque.push_back(3).unwrap();
+        deque.push_front(1).unwrap();
+
+        assert_eq!(deque.peek_front_nth(0), Some(&1));
+        assert_eq!(deque.peek_front_nth(2), Some(&3));
+        assert_eq!(deque.peek_front_nth(3), None);
+
+        *deque.peek_front_nth_mut(2).unwrap() = 30;
+        assert_eq!(deque.back(), Some(&30));
+        assert!(deque.peek_front_nth_mut(3).is_none());
+    }
+
     #[test]
     fn test_back_mut() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -573,6 +1323,42 @@ mod tests {
         assert!(deque.is_empty());
     }
 
+    #[test]
+    fn test_keep_last() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut deque = VecDeque::new_in(wma.clone());
+        for value in 1..=6 {
+            deque.push_back(value).unwrap();
+        }
+
+        deque.keep_last(2);
+        assert_eq!(deque.len(), 2);
+        assert_eq!(deque.get(0), Some(&5));
+        assert_eq!(deque.get(1), Some(&6));
+
+        deque.keep_last(3);
+        assert_eq!(deque.len(), 2);
+    }
+
+    #[test]
+    fn test_keep_first() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(128);
+        let mut deque = VecDeque::new_in(wma.clone());
+        for value in 1..=6 {
+            deque.push_back(value).unwrap();
+        }
+
+        deque.keep_first(2);
+        assert_eq!(deque.len(), 2);
+        assert_eq!(deque.get(0), Some(&1));
+        assert_eq!(deque.get(1), Some(&2));
+
+        deque.keep_first(3);
+        assert_eq!(deque.len(), 2);
+    }
+
     #[test]
     fn test_make_contiguous() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();
@@ -638,6 +1424,36 @@ mod tests {
         assert!(cloned.is_err());
     }
 
+    #[test]
+    fn test_try_clone_from_reuses_capacity() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let ca = CountingAllocator::new();
+        let mut original = VecDeque::new_in(ca.clone());
+        original.extend([1, 2, 3]).unwrap();
+        let mut target = VecDeque::with_capacity_in(8, ca.clone()).unwrap();
+        target.extend([10, 20]).unwrap();
+        let allocations = ca.allocations();
+
+        target.try_clone_from(&original).unwrap();
+        assert_eq!(target.make_contiguous(), &[1, 2, 3]);
+        assert_eq!(target.capacity(), 8);
+        assert_eq!(ca.allocations(), allocations);
+        assert_eq!(ca.grows(), 0);
+    }
+
+    #[test]
+    fn test_try_clone_from_failure() {
+        let _no_global_alloc_guard = NoGlobalAllocGuard::new();
+        let wma = WatermarkAllocator::new(32);
+        let mut original = VecDeque::new_in(wma.clone());
+        original.extend([1u32, 2, 3, 4, 5]).unwrap();
+        let mut target = VecDeque::with_capacity_in(1, wma.clone()).unwrap();
+        target.push_back(10).unwrap();
+
+        assert!(target.try_clone_from(&original).is_err());
+        assert_eq!(target.make_contiguous(), &[10]);
+    }
+
     #[test]
     fn test_try_clone_from_success() {
         let _no_global_alloc_guard = NoGlobalAllocGuard::new();